@@ -0,0 +1,223 @@
+use std::f64::consts::LN_10;
+
+use crate::expr::{binary, Expr, Op};
+use crate::functions::MathFn;
+use crate::type_enum::Type;
+
+impl Expr {
+    /// Returns the derivative of the expression with respect to the variable `var`.
+    ///
+    /// The derivative covers the arithmetic operators, powers and the built-in math functions.
+    /// Terms are simplified as they are built, e.g. multiplications by `0` or `1` are removed
+    /// and operations on constants are folded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_little_eval::expr::Expr;
+    ///
+    /// let expr = Expr::parse("x^3 + 2*x").unwrap();
+    ///
+    /// assert_eq!(expr.derivative("x").unwrap().to_string(), "3*x^2 + 2");
+    /// ```
+    pub fn derivative(&self, var: &str) -> Result<Expr, String> {
+        if !self.contains_var(var) {
+            return Ok(int(0));
+        }
+        Ok(match self {
+            Expr::Value(_) => int(0),
+            Expr::Var(_) => int(1),
+            Expr::Neg(a) => neg(a.derivative(var)?),
+            Expr::BinOp(op, a, b) => {
+                let (a, b) = (a.as_ref().clone(), b.as_ref().clone());
+                let (da, db) = (a.derivative(var)?, b.derivative(var)?);
+                match op {
+                    Op::Add => add(da, db),
+                    Op::Sub => sub(da, db),
+                    Op::Mul => add(mul(da, b), mul(a, db)),
+                    Op::Div => div(sub(mul(da, b.clone()), mul(a, db)), pow(b, int(2))),
+                    Op::Rem if !b.contains_var(var) => da,
+                    Op::Pow if !b.contains_var(var) => {
+                        mul(mul(b.clone(), pow(a, sub(b, int(1)))), da)
+                    }
+                    Op::Pow if !a.contains_var(var) => mul(mul(self.clone(), call("ln", a)), db),
+                    Op::Pow => mul(
+                        self.clone(),
+                        add(mul(db, call("ln", a.clone())), div(mul(b, da), a)),
+                    ),
+                    _ => {
+                        return Err(format!(
+                            "Unable to differentiate {} with respect to {}",
+                            self, var
+                        ))
+                    }
+                }
+            }
+            Expr::Call(name, args) => match (MathFn::from_name(name), args.as_slice()) {
+                (Some(f), [a]) => mul(a.derivative(var)?, function_derivative(f, a.clone())),
+                _ => {
+                    return Err(format!(
+                        "Unable to differentiate {} with respect to {}",
+                        self, var
+                    ))
+                }
+            },
+            Expr::Interval(_, _) | Expr::List(_) | Expr::Factorial(_) | Expr::Let(_, _, _) => {
+                return Err(format!(
+                    "Unable to differentiate {} with respect to {}",
+                    self, var
+                ))
+            }
+        })
+    }
+}
+
+/// Returns the derivative of `f` at `a`.
+fn function_derivative(f: MathFn, a: Expr) -> Expr {
+    match f {
+        MathFn::Abs => div(call("abs", a.clone()), a),
+        MathFn::Sqrt => div(int(1), mul(int(2), call("sqrt", a))),
+        MathFn::Exp => call("exp", a),
+        MathFn::Ln => div(int(1), a),
+        MathFn::Log10 => div(int(1), mul(a, Expr::Value(Type::Float(LN_10)))),
+        MathFn::Sin => call("cos", a),
+        MathFn::Cos => neg(call("sin", a)),
+        MathFn::Tan => div(int(1), pow(call("cos", a), int(2))),
+    }
+}
+
+fn int(value: i32) -> Expr {
+    Expr::Value(Type::Int(value))
+}
+
+fn is_int(expr: &Expr, value: i32) -> bool {
+    match expr {
+        Expr::Value(Type::Int(a)) => *a == value,
+        Expr::Value(Type::Float(a)) => *a == value as f64,
+        _ => false,
+    }
+}
+
+fn call(name: &str, a: Expr) -> Expr {
+    Expr::Call(name.to_string(), vec![a])
+}
+
+fn neg(a: Expr) -> Expr {
+    match a {
+        Expr::Value(value) => match -value.clone() {
+            Ok(value) => Expr::Value(value),
+            Err(_) => Expr::Neg(Box::new(Expr::Value(value))),
+        },
+        Expr::Neg(a) => *a,
+        Expr::BinOp(Op::Mul, a, b) if matches!(*a, Expr::Value(_)) => mul(neg(*a), *b),
+        a => Expr::Neg(Box::new(a)),
+    }
+}
+
+fn add(a: Expr, b: Expr) -> Expr {
+    if is_int(&a, 0) {
+        b
+    } else if is_int(&b, 0) {
+        a
+    } else if let Expr::Neg(b) = b {
+        sub(a, *b)
+    } else {
+        binary(Op::Add, a, b)
+    }
+}
+
+fn sub(a: Expr, b: Expr) -> Expr {
+    if is_int(&b, 0) {
+        a
+    } else if is_int(&a, 0) {
+        neg(b)
+    } else if a == b {
+        int(0)
+    } else {
+        binary(Op::Sub, a, b)
+    }
+}
+
+fn mul(a: Expr, b: Expr) -> Expr {
+    if is_int(&a, 0) || is_int(&b, 0) {
+        int(0)
+    } else if is_int(&a, 1) {
+        b
+    } else if is_int(&b, 1) {
+        a
+    } else if is_int(&a, -1) {
+        neg(b)
+    } else if let Expr::Neg(a) = a {
+        neg(mul(*a, b))
+    } else if let Expr::Neg(b) = b {
+        neg(mul(a, *b))
+    } else if matches!(b, Expr::Value(_)) && !matches!(a, Expr::Value(_)) {
+        // constant factors go first, e.g. `3*x` instead of `x*3`
+        mul(b, a)
+    } else {
+        binary(Op::Mul, a, b)
+    }
+}
+
+fn div(a: Expr, b: Expr) -> Expr {
+    if is_int(&b, 1) {
+        a
+    } else if is_int(&a, 0) {
+        int(0)
+    } else if let Expr::Neg(a) = a {
+        neg(div(*a, b))
+    } else {
+        binary(Op::Div, a, b)
+    }
+}
+
+fn pow(a: Expr, b: Expr) -> Expr {
+    if is_int(&b, 0) {
+        int(1)
+    } else if is_int(&b, 1) {
+        a
+    } else {
+        binary(Op::Pow, a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_derivative(equation: &str, derivative: &str) -> Result<(), String> {
+        assert_eq!(
+            Expr::parse(equation)?.derivative("x")?.to_string(),
+            derivative,
+            "\n d/dx {}",
+            equation
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn polynomials() -> Result<(), String> {
+        assert_derivative("5", "0")?;
+        assert_derivative("y * x", "y")?;
+        assert_derivative("x^2 - 3*x + 2", "2*x - 3")?;
+        assert_derivative("-x^-1", "x^-2")?;
+        Ok(())
+    }
+
+    #[test]
+    fn rules() -> Result<(), String> {
+        assert_derivative("x * sin(x)", "sin(x) + x*cos(x)")?;
+        assert_derivative("1 / x", "-1/x^2")?;
+        assert_derivative("2^x", "2^x*ln(2)")?;
+        assert_derivative("exp(2*x)", "2*exp(2*x)")?;
+        assert_derivative("x^x", "x^x*(ln(x) + x/x)")?;
+        assert_derivative("cos(x^2)", "-(2*x*sin(x^2))")?;
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported() {
+        assert!(Expr::parse("x % x").unwrap().derivative("x").is_err());
+        assert!(Expr::parse("f(x)").unwrap().derivative("x").is_err());
+    }
+}
@@ -0,0 +1,264 @@
+use std::f64::consts::PI;
+
+use crate::functions;
+use crate::type_enum::Type;
+
+/// The coefficients of the Lanczos approximation with `g = 7`.
+const LANCZOS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+/// The gamma function of arguments beyond this is too large for a `f64`.
+const MAX_GAMMA: f64 = 171.624_376_956_302_7;
+
+/// Fibonacci numbers beyond this index are too large for a `f64`.
+const MAX_FIBONACCI: i32 = 1_476;
+
+/// Calls the combinatorics function `name`, returning `None` if there is no such function.
+pub(crate) fn call(name: &str, args: &[Type]) -> Option<Result<Type, String>> {
+    let arity = match name {
+        "factorial" | "fib" | "gamma" => 1..=1,
+        "nCr" | "nPr" | "binomial" => 2..=2,
+        _ => return None,
+    };
+    Some(functions::check_arity(name, args, arity).and_then(|()| apply(name, args)))
+}
+
+fn apply(name: &str, args: &[Type]) -> Result<Type, String> {
+    match (name, args) {
+        ("factorial", [n]) => factorial(n.clone()),
+        ("fib", [Type::Int(n)]) => fibonacci(*n),
+        ("gamma", [x]) => match x.as_f64() {
+            Some(x) => Ok(Type::Float(gamma(x)?)),
+            None => Err(format!("Unable to calculate gamma of {}", x.get_type())),
+        },
+        ("nCr" | "binomial", [Type::Int(n), Type::Int(r)]) => choose(*n, *r, false),
+        ("nPr", [Type::Int(n), Type::Int(r)]) => choose(*n, *r, true),
+        ("binomial", [n, k]) => match (n.as_f64(), k.as_f64()) {
+            // generalized to real numbers by the gamma function
+            (Some(n), Some(k)) if is_pole(k + 1.0) || is_pole(n - k + 1.0) => Ok(Type::Float(0.0)),
+            (Some(n), Some(k)) => {
+                let value = gamma(n + 1.0)? / (gamma(k + 1.0)? * gamma(n - k + 1.0)?);
+                if value.is_nan() {
+                    return Err(format!(
+                        "Unable to calculate {}({}, {}), the gamma function overflows",
+                        name, n, k
+                    ));
+                }
+                Ok(Type::Float(value))
+            }
+            _ => Err(format!(
+                "{} expects numbers but got {} and {}",
+                name,
+                n.get_type(),
+                k.get_type()
+            )),
+        },
+        (name, args) => Err(format!(
+            "{} expects integers but got {}",
+            name,
+            args.iter()
+                .map(Type::get_type)
+                .collect::<Vec<&str>>()
+                .join(" and ")
+        )),
+    }
+}
+
+/// Returns `true` for the non-positive integers, where the gamma function has its poles.
+fn is_pole(x: f64) -> bool {
+    x <= 0.0 && x.fract() == 0.0
+}
+
+/// Calculates `n!`, promoting integers to floats if the result doesn't fit into an `i32`,
+/// and using the gamma function for non-integer floats.
+pub(crate) fn factorial(n: Type) -> Result<Type, String> {
+    match n {
+        Type::Int(n) if n < 0 => {
+            Err("Unable to calculate the factorial of a negative number".to_string())
+        }
+        Type::Int(n) => {
+            let mut result: i32 = 1;
+            for i in 2..=n {
+                match result.checked_mul(i) {
+                    Some(value) => result = value,
+                    None => return Ok(Type::Float(gamma(n as f64 + 1.0)?)),
+                }
+            }
+            Ok(Type::Int(result))
+        }
+        Type::Float(x) => Ok(Type::Float(gamma(x + 1.0).map_err(|_| {
+            "Unable to calculate the factorial of a negative integer".to_string()
+        })?)),
+        n => Err(format!(
+            "Unable to calculate the factorial of {}",
+            n.get_type()
+        )),
+    }
+}
+
+/// Calculates the gamma function with the Lanczos approximation, using the reflection formula for `x < 0.5`.
+///
+/// Results beyond the range of a `f64` are infinite.
+fn gamma(x: f64) -> Result<f64, String> {
+    if is_pole(x) {
+        return Err("Unable to calculate the gamma function of a non-positive integer".to_string());
+    }
+    if x < 0.5 {
+        return Ok(PI / ((PI * x).sin() * gamma(1.0 - x)?));
+    }
+    if x > MAX_GAMMA {
+        return Ok(f64::INFINITY);
+    }
+    if x.fract() == 0.0 && x <= 171.0 {
+        // exact for integers, as long as the product is representable
+        return Ok((2..x as u32).fold(1.0, |acc, i| acc * i as f64));
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let sum = LANCZOS[1..]
+        .iter()
+        .enumerate()
+        .fold(LANCZOS[0], |acc, (i, c)| acc + c / (x + i as f64 + 1.0));
+    // split the power to delay the overflow for large arguments
+    let power = t.powf((x + 0.5) / 2.0);
+    Ok((2.0 * PI).sqrt() * power * (sum * (-t).exp()) * power)
+}
+
+/// Calculates the number of combinations `nCr`, or permutations `nPr` if `ordered` is set,
+/// promoting the result to a float if it doesn't fit into an `i32`.
+fn choose(n: i32, r: i32, ordered: bool) -> Result<Type, String> {
+    if n < 0 {
+        return Err("Unable to choose from a negative number of elements".to_string());
+    }
+    if r < 0 {
+        return Err("Unable to choose a negative number of elements".to_string());
+    }
+    if r > n {
+        return Ok(Type::Int(0));
+    }
+    let (n, r) = (n as i64, if ordered { r } else { r.min(n - r) } as i64);
+    let mut i = 0;
+    let mut exact: i64 = 1;
+    while i < r && exact <= i32::MAX as i64 {
+        // the product of i + 1 consecutive numbers is divisible by (i + 1)!
+        exact *= n - i;
+        if !ordered {
+            exact /= i + 1;
+        }
+        i += 1;
+    }
+    if exact <= i32::MAX as i64 {
+        return Ok(Type::Int(exact as i32));
+    }
+    let mut approximation = exact as f64;
+    while i < r && approximation.is_finite() {
+        approximation *= (n - i) as f64;
+        if !ordered {
+            approximation /= (i + 1) as f64;
+        }
+        i += 1;
+    }
+    Ok(Type::Float(approximation))
+}
+
+/// Calculates the `n`th Fibonacci number, promoting integers to floats if the result doesn't fit into an `i32`.
+fn fibonacci(n: i32) -> Result<Type, String> {
+    if n < 0 {
+        return Err("Unable to calculate the Fibonacci number of a negative index".to_string());
+    }
+    if n > MAX_FIBONACCI {
+        return Ok(Type::Float(f64::INFINITY));
+    }
+    let (mut a, mut b) = (0u128, 1u128);
+    for _ in 0..n.min(186) {
+        (a, b) = (b, a + b);
+    }
+    if let Ok(value) = i32::try_from(a) {
+        return Ok(Type::Int(value));
+    }
+    let (mut a, mut b) = (0.0f64, 1.0f64);
+    for _ in 0..n {
+        (a, b) = (b, a + b);
+    }
+    Ok(Type::Float(a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn factorials() -> Result<(), String> {
+        assert_eq!(factorial(Type::Int(0))?, Type::Int(1));
+        assert_eq!(factorial(Type::Int(12))?, Type::Int(479001600));
+        assert_eq!(factorial(Type::Int(13))?, Type::Float(6227020800.0));
+        assert!(factorial(Type::Int(-1)).is_err());
+
+        let Type::Float(half) = factorial(Type::Float(0.5))? else {
+            panic!("factorials of floats should be floats");
+        };
+        assert!((half - PI.sqrt() / 2.0).abs() < 1e-14);
+        assert!(factorial(Type::Float(-2.0)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn gamma_function() -> Result<(), String> {
+        assert_eq!(gamma(5.0)?, 24.0);
+        assert!((gamma(-0.5)? + 2.0 * PI.sqrt()).abs() < 1e-13);
+        assert!((gamma(10.5)? / (654_729_075.0 / 1024.0 * PI.sqrt()) - 1.0).abs() < 1e-14);
+        assert!(gamma(0.0).is_err());
+
+        assert!(gamma(171.6)?.is_finite());
+        assert_eq!(gamma(171.7)?, f64::INFINITY);
+        assert_eq!(gamma(-1000.5)?, 0.0);
+        assert_eq!(factorial(Type::Int(1000))?, Type::Float(f64::INFINITY));
+        assert_eq!(factorial(Type::Int(i32::MAX))?, Type::Float(f64::INFINITY));
+        assert!(functions::call("binomial", vec![Type::Float(1000.5), Type::Int(1)]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn combinations() -> Result<(), String> {
+        let ints = |a: i32, b: i32| vec![Type::Int(a), Type::Int(b)];
+        assert_eq!(functions::call("nCr", ints(5, 2))?, Type::Int(10));
+        assert_eq!(functions::call("nPr", ints(5, 2))?, Type::Int(20));
+        assert_eq!(functions::call("binomial", ints(2, 5))?, Type::Int(0));
+        assert_eq!(functions::call("nCr", ints(33, 16))?, Type::Int(1166803110));
+        assert_eq!(
+            functions::call("nCr", ints(40, 20))?,
+            Type::Float(137846528820.0)
+        );
+        let Type::Float(half) = functions::call("binomial", vec![Type::Float(0.5), Type::Int(1)])?
+        else {
+            panic!("generalized binomial coefficients should be floats");
+        };
+        assert!((half - 0.5).abs() < 1e-14);
+        assert_eq!(
+            functions::call("binomial", vec![Type::Float(2.0), Type::Float(5.0)])?,
+            Type::Float(0.0)
+        );
+        assert!(functions::call("nCr", ints(-1, 2)).is_err());
+        assert!(functions::call("nCr", vec![Type::Float(5.0), Type::Int(2)]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn fibonacci_numbers() -> Result<(), String> {
+        assert_eq!(fibonacci(0)?, Type::Int(0));
+        assert_eq!(fibonacci(46)?, Type::Int(1836311903));
+        assert_eq!(fibonacci(47)?, Type::Float(2971215073.0));
+        assert_eq!(fibonacci(2000)?, Type::Float(f64::INFINITY));
+        assert!(fibonacci(-1).is_err());
+        Ok(())
+    }
+}
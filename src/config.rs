@@ -0,0 +1,219 @@
+use std::fs;
+use std::path::Path;
+
+use crate::expr::Expr;
+use crate::resolver::Resolver;
+use crate::type_enum::Type;
+
+/// Settings for evaluating equations and printing their results.
+///
+/// Configs are read from files of `key = value` lines, where `#` starts a comment:
+///
+/// ```text
+/// precision = 4   # decimal places of floats
+/// strict = true   # unknown variables are errors instead of strings
+/// base = 16       # base of printed integers: 2, 8, 10 or 16
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::{config::Config, type_enum::Type};
+///
+/// let config = Config::parse("precision = 2\nbase = 16").unwrap();
+/// assert_eq!(config.format(&Type::Float(2.0 / 3.0)), "0.67");
+/// assert_eq!(config.format(&Type::Int(255)), "0xff");
+///
+/// let strict = Config::parse("strict = true").unwrap();
+/// assert_eq!(strict.eval("x + 1", None), Err("Unknown variable x".to_string()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// The number of decimal places floats are printed with, or `None` to print them as short as possible.
+    pub precision: Option<usize>,
+    /// Whether unknown variables are errors instead of evaluating to a string of their name.
+    ///
+    /// This applies to evaluated equations and to formulas when they are defined, see `Config::check`,
+    /// but not to sheets, which are calculated on their own.
+    pub strict: bool,
+    /// The base integers are printed in, one of 2, 8, 10 or 16.
+    pub base: u32,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            precision: None,
+            strict: false,
+            base: 10,
+        }
+    }
+}
+
+impl Config {
+    /// Parses a config of `key = value` lines, missing settings keep their default.
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let mut config = Config::default();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            config
+                .set_line(line)
+                .map_err(|err| format!("line {}: {}", idx + 1, err))?;
+        }
+        Ok(config)
+    }
+
+    /// Loads a config from the file at `path`, see `Config::parse`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Config, String> {
+        let text = fs::read_to_string(&path)
+            .map_err(|err| format!("Unable to read {}: {}", path.as_ref().display(), err))?;
+        Config::parse(&text).map_err(|err| format!("{}: {}", path.as_ref().display(), err))
+    }
+
+    /// Changes the setting `key` to `value`, e.g. `set("base", "16")`.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "precision" => {
+                self.precision = match value.parse() {
+                    Ok(precision) if precision <= 17 => Some(precision),
+                    _ => return Err("precision must be an integer from 0 to 17".to_string()),
+                }
+            }
+            "strict" => {
+                self.strict = value
+                    .parse()
+                    .map_err(|_| "strict must be true or false".to_string())?
+            }
+            "base" => {
+                self.base = match value.parse() {
+                    Ok(base @ (2 | 8 | 10 | 16)) => base,
+                    _ => return Err("base must be 2, 8, 10 or 16".to_string()),
+                }
+            }
+            _ => return Err(format!("Unknown setting {}", key)),
+        }
+        Ok(())
+    }
+
+    fn set_line(&mut self, line: &str) -> Result<(), String> {
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("{} is missing '='", line));
+        };
+        self.set(key.trim(), value.trim().trim_matches('"'))
+    }
+
+    /// Evaluates `equation` like `eval`, but rejects unknown variables in strict mode.
+    pub fn eval(&self, equation: &str, vars: Option<&dyn Resolver>) -> Result<Type, String> {
+        let expr = Expr::parse(equation)?;
+        self.check(&expr, |name| vars.and_then(|vars| vars.get(name)).is_some())?;
+        expr.eval(vars)
+    }
+
+    /// Rejects the first free variable of `expr` for which `is_known` is `false` in strict mode.
+    pub fn check(&self, expr: &Expr, is_known: impl Fn(&str) -> bool) -> Result<(), String> {
+        if !self.strict {
+            return Ok(());
+        }
+        match expr
+            .free_variables()
+            .into_iter()
+            .find(|name| !is_known(name))
+        {
+            Some(name) => Err(format!("Unknown variable {}", name)),
+            None => Ok(()),
+        }
+    }
+
+    /// Formats `value` with the configured precision and base.
+    ///
+    /// The precision applies to floats, the base to integers, also within lists.
+    /// All other values are formatted like their `Display` implementation.
+    pub fn format(&self, value: &Type) -> String {
+        match value {
+            Type::Int(value) => {
+                let sign = if *value < 0 { "-" } else { "" };
+                let value = value.unsigned_abs();
+                match self.base {
+                    2 => format!("{}0b{:b}", sign, value),
+                    8 => format!("{}0o{:o}", sign, value),
+                    16 => format!("{}0x{:x}", sign, value),
+                    _ => format!("{}{}", sign, value),
+                }
+            }
+            Type::Float(value) => match self.precision {
+                Some(precision) => format!("{:.*}", precision, value),
+                None => value.to_string(),
+            },
+            Type::List(values) => format!(
+                "[{}]",
+                values
+                    .iter()
+                    .map(|value| self.format(value))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            value => value.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn parse_settings() -> Result<(), String> {
+        let config = Config::parse(
+            "# settings\n\nprecision = 3\nstrict = true # no strings\nbase = \"2\"\n",
+        )?;
+        assert_eq!(
+            config,
+            Config {
+                precision: Some(3),
+                strict: true,
+                base: 2
+            }
+        );
+        assert_eq!(
+            Config::parse("precision = 2\ncolor = red"),
+            Err("line 2: Unknown setting color".to_string())
+        );
+        assert!(Config::parse("base = 7").is_err());
+        assert!(Config::parse("strict").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn format_values() {
+        let config = Config {
+            precision: Some(2),
+            strict: false,
+            base: 8,
+        };
+        let list = Type::List(vec![Type::Int(-8), Type::Float(0.125), Type::from("x")]);
+        assert_eq!(config.format(&list), "[-0o10, 0.12, \"x\"]");
+        assert_eq!(Config::default().format(&list), list.to_string());
+    }
+
+    #[test]
+    fn strict_eval() {
+        let vars = HashMap::from([("x".to_string(), Type::Int(2))]);
+        let strict = Config {
+            strict: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            strict.eval("x * pi / pi", Some(&vars)),
+            Ok(Type::Float(2.0))
+        );
+        assert_eq!(
+            strict.eval("let y = 1 in x + y + z", Some(&vars)),
+            Err("Unknown variable z".to_string())
+        );
+        assert_eq!(Config::default().eval("z", None), Ok(Type::from("z")));
+    }
+}
@@ -0,0 +1,94 @@
+use std::f64::consts::{E, PI, TAU};
+
+use crate::type_enum::Type;
+
+/// The built-in constants as `(name, value, description)`,
+/// physical constants in SI units as defined by CODATA 2018.
+pub const CONSTANTS: &[(&str, f64, &str)] = &[
+    (
+        "pi",
+        PI,
+        "ratio of a circle's circumference to its diameter",
+    ),
+    ("e", E, "Euler's number"),
+    (
+        "tau",
+        TAU,
+        "ratio of a circle's circumference to its radius",
+    ),
+    ("inf", f64::INFINITY, "positive infinity"),
+    ("nan", f64::NAN, "not a number"),
+    (
+        "speed_of_light",
+        299_792_458.0,
+        "speed of light in vacuum in m/s",
+    ),
+    ("planck", 6.626_070_15e-34, "Planck constant in J*s"),
+    ("hbar", 1.054_571_817e-34, "reduced Planck constant in J*s"),
+    ("boltzmann", 1.380_649e-23, "Boltzmann constant in J/K"),
+    ("avogadro", 6.022_140_76e23, "Avogadro constant in 1/mol"),
+    (
+        "elementary_charge",
+        1.602_176_634e-19,
+        "elementary charge in C",
+    ),
+    (
+        "gas_constant",
+        8.314_462_618,
+        "molar gas constant in J/(mol*K)",
+    ),
+    (
+        "gravitational_constant",
+        6.674_30e-11,
+        "Newtonian constant of gravitation in m^3/(kg*s^2)",
+    ),
+    (
+        "standard_gravity",
+        9.806_65,
+        "standard acceleration of gravity in m/s^2",
+    ),
+    ("electron_mass", 9.109_383_701_5e-31, "electron mass in kg"),
+    ("proton_mass", 1.672_621_923_69e-27, "proton mass in kg"),
+    (
+        "vacuum_permittivity",
+        8.854_187_812_8e-12,
+        "vacuum electric permittivity in F/m",
+    ),
+];
+
+/// Returns the value of the built-in constant `name`, or `None` if there is no such constant.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::{constants, type_enum::Type};
+///
+/// assert_eq!(constants::get("pi"), Some(Type::Float(std::f64::consts::PI)));
+/// assert_eq!(constants::get("x"), None);
+/// ```
+pub fn get(name: &str) -> Option<Type> {
+    CONSTANTS
+        .iter()
+        .find(|(constant, _, _)| *constant == name)
+        .map(|(_, value, _)| Type::Float(*value))
+}
+
+/// Returns `true` if `name` is a built-in constant, which should be treated as read-only.
+pub fn is_constant(name: &str) -> bool {
+    CONSTANTS.iter().any(|(constant, _, _)| *constant == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup() {
+        assert_eq!(get("tau"), Some(Type::Float(2.0 * PI)));
+        assert_eq!(get("avogadro"), Some(Type::Float(6.022_140_76e23)));
+        assert!(matches!(get("nan"), Some(Type::Float(value)) if value.is_nan()));
+        assert!(is_constant("e"));
+        assert!(!is_constant("E"));
+        assert_eq!(get("res"), None);
+    }
+}
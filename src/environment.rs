@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use crate::functions;
+use crate::type_enum::Type;
+
+/// Returns the environment variables whose names start with `prefix` as variables named without the prefix,
+/// e.g. `CALC_rate=0.05` becomes `rate` with `prefix` `CALC_`.
+///
+/// Values are converted with `Type::from`, so numbers, intervals and measurements keep their types.
+/// Names that can't be used as variables and values that aren't valid unicode are skipped.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::{environment, type_enum::Type};
+///
+/// std::env::set_var("DOC_CALC_rate", "0.05");
+/// let vars = environment::vars_from_env("DOC_CALC_");
+/// assert_eq!(vars["rate"], Type::Float(0.05));
+/// ```
+pub fn vars_from_env(prefix: &str) -> HashMap<String, Type> {
+    env::vars_os()
+        .filter_map(|(name, value)| {
+            let name = name.to_str()?.strip_prefix(prefix)?;
+            let value = value.to_str()?;
+            is_var_name(name).then(|| (name.to_string(), Type::from(value)))
+        })
+        .collect()
+}
+
+/// Parses the `NAME=value` lines of a `.env` file in order.
+///
+/// Empty lines and lines starting with `#` are skipped, as is a leading `export`.
+/// Values may be quoted with `'`, taken literally, or `"`, where `\n`, `\"` and `\\` are escaped.
+/// Unquoted values end at a ` #` comment.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::environment;
+///
+/// let entries = environment::parse_dotenv("# rates\nexport RATE=0.05\nNAME=\"my loan\"").unwrap();
+/// assert_eq!(
+///     entries,
+///     [("RATE".to_string(), "0.05".to_string()), ("NAME".to_string(), "my loan".to_string())]
+/// );
+/// ```
+pub fn parse_dotenv(text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut entries = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let entry = parse_entry(line).map_err(|err| format!("line {}: {}", idx + 1, err))?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Loads the entries of the `.env` file at `path`, see `parse_dotenv`.
+pub fn load_dotenv(path: impl AsRef<Path>) -> Result<Vec<(String, String)>, String> {
+    let text = fs::read_to_string(&path)
+        .map_err(|err| format!("Unable to read {}: {}", path.as_ref().display(), err))?;
+    parse_dotenv(&text).map_err(|err| format!("{}: {}", path.as_ref().display(), err))
+}
+
+fn parse_entry(line: &str) -> Result<(String, String), String> {
+    let Some((name, value)) = line.split_once('=') else {
+        return Err(format!("{} is missing '='", line));
+    };
+    let name = name.trim();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(format!("{} is not a valid name", name));
+    }
+    let value = value.trim();
+    let value = if let Some(quoted) = value.strip_prefix('\'') {
+        match quoted.split_once('\'') {
+            Some((value, _)) => value.to_string(),
+            None => return Err(format!("The value of {} must be closed with '", name)),
+        }
+    } else if let Some(quoted) = value.strip_prefix('"') {
+        let mut unescaped = String::new();
+        let mut chars = quoted.chars();
+        loop {
+            match chars.next() {
+                Some('"') => break,
+                Some('\\') => match chars.next() {
+                    Some('n') => unescaped.push('\n'),
+                    Some(c) => unescaped.push(c),
+                    None => (),
+                },
+                Some(c) => unescaped.push(c),
+                None => return Err(format!("The value of {} must be closed with \"", name)),
+            }
+        }
+        unescaped
+    } else {
+        match value.split_once(" #") {
+            Some((value, _)) => value.trim_end().to_string(),
+            None => value.to_string(),
+        }
+    };
+    Ok((name.to_string(), value))
+}
+
+fn is_var_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Calls the function `env`, returning `None` for any other function.
+///
+/// `env("NAME")` reads the environment variable `NAME` with `Type::from`,
+/// `env("NAME", default)` results in `default` if it is not set.
+pub(crate) fn call(name: &str, args: &[Type]) -> Option<Result<Type, String>> {
+    if name != "env" {
+        return None;
+    }
+    if let Err(err) = functions::check_arity(name, args, 1..=2) {
+        return Some(Err(err));
+    }
+    let (var, default) = match args {
+        [Type::String(var)] => (var, None),
+        [Type::String(var), default] => (var, Some(default)),
+        _ => return Some(Err("env expects the name of a variable".to_string())),
+    };
+    Some(match (env::var(var), default) {
+        (Ok(value), _) => Ok(Type::from(value.as_str())),
+        (Err(_), Some(default)) => Ok(default.clone()),
+        (Err(_), None) => Err(format!("The environment variable {} is not set", var)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval;
+
+    #[test]
+    fn env_function() {
+        env::set_var("MY_LITTLE_EVAL_TEST_G", "9.81 ± 0.02");
+        assert_eq!(
+            eval("env(\"MY_LITTLE_EVAL_TEST_G\") * 2", None).map(|g| g.to_string()),
+            Ok("19.62 ± 0.04".to_string())
+        );
+        assert_eq!(
+            eval("env(\"MY_LITTLE_EVAL_TEST_MISSING\", 3) + 1", None),
+            Ok(Type::Int(4))
+        );
+        assert!(eval("env(\"MY_LITTLE_EVAL_TEST_MISSING\")", None).is_err());
+        assert!(eval("env(1)", None).is_err());
+    }
+
+    #[test]
+    fn prefixed_vars() {
+        env::set_var("MY_LITTLE_EVAL_TEST_qty", "12");
+        env::set_var("MY_LITTLE_EVAL_TEST_1", "1");
+        let vars = vars_from_env("MY_LITTLE_EVAL_TEST_");
+        assert_eq!(vars.get("qty"), Some(&Type::Int(12)));
+        assert_eq!(vars.get("1"), None);
+    }
+
+    #[test]
+    fn dotenv() -> Result<(), String> {
+        let text = "A=1 # one\nB='x # y'\n\n  C = \"a\\\"b\\nc\" # quoted\nD=";
+        assert_eq!(
+            parse_dotenv(text)?,
+            [
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "x # y".to_string()),
+                ("C".to_string(), "a\"b\nc".to_string()),
+                ("D".to_string(), String::new()),
+            ]
+        );
+        assert_eq!(
+            parse_dotenv("A=1\nB"),
+            Err("line 2: B is missing '='".to_string())
+        );
+        assert!(parse_dotenv("A=\"open").is_err());
+        Ok(())
+    }
+}
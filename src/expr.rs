@@ -0,0 +1,752 @@
+use std::cell::Cell;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::ops::Neg;
+use std::str::FromStr;
+
+use crate::combinatorics;
+use crate::constants;
+use crate::functions;
+use crate::integrate;
+use crate::interval::Interval;
+use crate::measurement::Measurement;
+use crate::ode;
+use crate::optimize;
+use crate::parser;
+use crate::polynomial::Polynomial;
+use crate::resolver::{Resolver, Scope};
+use crate::solve;
+use crate::type_enum::Type;
+
+/// Represents a binary operator of an `Expr`.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Op {
+    /// Addition `a + b`
+    Add,
+    /// Subtraction `a - b`
+    Sub,
+    /// Multiplication `a * b`
+    Mul,
+    /// Division `a / b`
+    Div,
+    /// Remainder `a % b`
+    Rem,
+    /// Exponentiation `a ^ b`
+    Pow,
+    /// Measurement with uncertainty `a ± b`
+    PlusMinus,
+    /// Equation `a = b`, which can be solved but not evaluated
+    Eq,
+}
+
+impl Op {
+    /// Applies the operator to two values.
+    pub fn apply(self, a: Type, b: Type) -> Result<Type, String> {
+        match self {
+            Op::Add => a + b,
+            Op::Sub => a - b,
+            Op::Mul => a * b,
+            Op::Div => a / b,
+            Op::Rem => a % b,
+            Op::Pow => a.pow(b),
+            Op::PlusMinus => {
+                let number = |value: Type| {
+                    value.as_f64().ok_or(format!(
+                        "Measurements must consist of numbers, not {}",
+                        value.get_type()
+                    ))
+                };
+                Ok(Type::Measurement(Measurement::new(number(a)?, number(b)?)?))
+            }
+            Op::Eq => {
+                Err("Equations can't be evaluated, solve them with solve(a = b, x)".to_string())
+            }
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Op::Add => " + ",
+            Op::Sub => " - ",
+            Op::Mul => "*",
+            Op::Div => "/",
+            Op::Rem => "%",
+            Op::Pow => "^",
+            Op::PlusMinus => " ± ",
+            Op::Eq => " = ",
+        }
+    }
+
+    fn precedence(self) -> u8 {
+        match self {
+            Op::Eq => 0,
+            Op::Add | Op::Sub => 1,
+            Op::Mul | Op::Div | Op::Rem => 2,
+            Op::Pow => 4,
+            Op::PlusMinus => 5,
+        }
+    }
+}
+
+/// Builds `a op b`, folding the operation if both operands are values.
+pub(crate) fn binary(op: Op, a: Expr, b: Expr) -> Expr {
+    if let (Expr::Value(x), Expr::Value(y)) = (&a, &b) {
+        if let Ok(value) = op.apply(x.clone(), y.clone()) {
+            return Expr::Value(value);
+        }
+    }
+    Expr::BinOp(op, Box::new(a), Box::new(b))
+}
+
+/// Represents an equation parsed into an abstract syntax tree.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::{expr::Expr, type_enum::Type};
+///
+/// let expr = Expr::parse("2 * (3 + 4)").unwrap();
+///
+/// assert_eq!(expr.to_string(), "2*(3 + 4)");
+/// assert_eq!(expr.eval(None), Ok(Type::Int(14)));
+/// ```
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum Expr {
+    /// A literal value, e.g. `3.14`
+    Value(Type),
+    /// A variable, which evaluates to a built-in constant like `pi` or a string of its name if it is not defined
+    Var(String),
+    /// A negation `-a`
+    Neg(Box<Expr>),
+    /// A factorial `a!`
+    Factorial(Box<Expr>),
+    /// A binary operation `a op b`
+    BinOp(Op, Box<Expr>, Box<Expr>),
+    /// An interval `[lo .. hi]`
+    Interval(Box<Expr>, Box<Expr>),
+    /// A function call `name(args)`
+    Call(String, Vec<Expr>),
+    /// A list `[a, b, c]`
+    List(Vec<Expr>),
+    /// A local binding `let name = value in body`, where `name` is only bound in `body`
+    Let(String, Box<Expr>, Box<Expr>),
+}
+
+/// A user defined function, e.g. `f` in `let f(x, y) = x^2 + y in f(1, 2)`.
+///
+/// Functions defined by `let ... in` are lexically scoped: they keep the values of the variables and functions
+/// they use from where they are defined, e.g. `let a = 10 in let f(x) = x + a in let a = 1 in f(1)` results in `11`.
+/// Names that are not defined there yet, like the function itself when it calls itself, are looked up when it is called.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::{eval, vars_init, expr::{Expr, Function}, type_enum::Type};
+///
+/// let square = Function::new(vec!["x".to_string()], Expr::parse("x^2").unwrap());
+/// assert_eq!(square.call(vec![Type::Int(3)], None), Ok(Type::Int(9)));
+///
+/// let mut variables = vars_init();
+/// variables.insert("square".to_string(), Type::Function(square));
+/// assert_eq!(eval("square(4) + 1", Some(&variables)), Ok(Type::Int(17)));
+/// ```
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct Function {
+    params: Vec<String>,
+    body: Box<Expr>,
+}
+
+impl Function {
+    /// Creates a function of the parameters `params`, which are bound when evaluating `body`.
+    pub fn new(params: Vec<String>, body: Expr) -> Function {
+        Function {
+            params,
+            body: Box::new(body),
+        }
+    }
+
+    /// Returns the names of the parameters.
+    pub fn params(&self) -> &[String] {
+        &self.params
+    }
+
+    /// Returns the expression the function evaluates.
+    pub fn body(&self) -> &Expr {
+        &self.body
+    }
+
+    /// Calls the function, binding the parameters to `args` in their own scope on top of `vars`.
+    ///
+    /// The body sees the variables of `vars` unless it captured them when it was defined by `let ... in`.
+    ///
+    /// Returns an error if the number of arguments does not match the number of parameters,
+    /// or if the calls are nested deeper than 100 levels, e.g. by a function calling itself.
+    pub fn call(&self, args: Vec<Type>, vars: Option<&dyn Resolver>) -> Result<Type, String> {
+        if args.len() != self.params.len() {
+            return Err(format!(
+                "Expected {} argument(s) but got {}",
+                self.params.len(),
+                args.len()
+            ));
+        }
+        let depth = CALL_DEPTH.with(|depth| depth.get());
+        if depth >= MAX_CALL_DEPTH {
+            return Err(format!(
+                "Unable to nest more than {} function calls",
+                MAX_CALL_DEPTH
+            ));
+        }
+        let mut scope = Scope::new(vars);
+        for (param, arg) in self.params.iter().zip(args) {
+            scope.set(param, arg);
+        }
+        CALL_DEPTH.with(|cell| cell.set(depth + 1));
+        let result = self.body.eval(Some(&scope));
+        CALL_DEPTH.with(|cell| cell.set(depth));
+        result
+    }
+}
+
+impl Function {
+    /// Captures the values of the variables and functions the body uses from `vars`,
+    /// by binding them around the body, so they can't be changed by the caller.
+    fn capture(self, vars: Option<&dyn Resolver>) -> Function {
+        let mut names = self.body.free_variables();
+        self.body.collect_calls(&mut names);
+        let mut body = *self.body;
+        for name in names.into_iter().rev() {
+            if self.params.contains(&name) {
+                continue;
+            }
+            if let Some(value) = vars.and_then(|vars| vars.get(&name)) {
+                body = Expr::Let(name, Box::new(Expr::Value(value)), Box::new(body));
+            }
+        }
+        Function::new(self.params, body)
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}) -> {}", self.params.join(", "), self.body)
+    }
+}
+
+/// The maximum number of nested calls of user defined functions.
+const MAX_CALL_DEPTH: usize = 100;
+
+thread_local! {
+    static CALL_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+impl Expr {
+    /// Parses an equation into an `Expr`.
+    ///
+    /// Returns an error if the equation is empty or malformed.
+    pub fn parse(equation: &str) -> Result<Expr, String> {
+        parser::parse(equation)
+    }
+
+    /// Evaluates the expression, substituting the variables from `vars`.
+    ///
+    /// Names that are not found in `vars` evaluate to the built-in constants or strings, just like in `eval`.
+    pub fn eval(&self, vars: Option<&dyn Resolver>) -> Result<Type, String> {
+        match self {
+            Expr::Value(value) => Ok(value.clone()),
+            Expr::Var(name) => Ok(var_or_string(name, vars)),
+            Expr::Neg(a) => a.eval(vars)?.neg(),
+            Expr::Factorial(a) => combinatorics::factorial(a.eval(vars)?),
+            Expr::BinOp(op, a, b) => op.apply(a.eval(vars)?, b.eval(vars)?),
+            Expr::Interval(lo, hi) => {
+                let bound = |bound: &Expr| {
+                    let value = bound.eval(vars)?;
+                    value.as_f64().ok_or(format!(
+                        "Interval bounds must be numbers, not {}",
+                        value.get_type()
+                    ))
+                };
+                Ok(Type::Interval(Interval::new(bound(lo)?, bound(hi)?)?))
+            }
+            Expr::Call(name, args) => call(name, args, vars),
+            Expr::List(items) => Ok(Type::List(
+                items
+                    .iter()
+                    .map(|item| item.eval(vars))
+                    .collect::<Result<Vec<Type>, String>>()?,
+            )),
+            Expr::Let(name, value, body) => {
+                let value = match value.eval(vars)? {
+                    Type::Function(function) => Type::Function(function.capture(vars)),
+                    value => value,
+                };
+                let mut scope = Scope::new(vars);
+                scope.set(name, value);
+                body.eval(Some(&scope))
+            }
+        }
+    }
+
+    /// Returns `true` if the variable `var` occurs in the expression.
+    pub fn contains_var(&self, var: &str) -> bool {
+        match self {
+            Expr::Value(_) => false,
+            Expr::Var(name) => name == var,
+            Expr::Neg(a) | Expr::Factorial(a) => a.contains_var(var),
+            Expr::BinOp(_, a, b) | Expr::Interval(a, b) => {
+                a.contains_var(var) || b.contains_var(var)
+            }
+            Expr::Call(_, args) | Expr::List(args) => args.iter().any(|arg| arg.contains_var(var)),
+            Expr::Let(name, value, body) => {
+                value.contains_var(var) || (name != var && body.contains_var(var))
+            }
+        }
+    }
+
+    /// Returns the names of the variables the expression needs to be evaluated,
+    /// i.e. every identifier that is looked up in the variables when evaluating it.
+    ///
+    /// Function names, built-in constants like `pi` and the variables bound by the expression itself,
+    /// e.g. by `let x = 2 in x` or the `x` of `integrate(x^2, x, 0, 1)`, are not included.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_little_eval::expr::Expr;
+    ///
+    /// let expr = Expr::parse("sqrt(a) * sum(i * b, i, 1, n) + pi").unwrap();
+    /// let names: Vec<String> = expr.free_variables().into_iter().collect();
+    /// assert_eq!(names, ["a", "b", "n"]);
+    /// ```
+    pub fn free_variables(&self) -> BTreeSet<String> {
+        let mut free = BTreeSet::new();
+        self.collect_free_variables(&[], &mut free);
+        free
+    }
+
+    fn collect_free_variables(&self, bound: &[&str], free: &mut BTreeSet<String>) {
+        match self {
+            Expr::Value(Type::Function(function)) => {
+                let mut bound = bound.to_vec();
+                bound.extend(function.params.iter().map(String::as_str));
+                function.body.collect_free_variables(&bound, free);
+            }
+            Expr::Value(_) => (),
+            Expr::Var(name) => {
+                if !bound.contains(&name.as_str()) && !constants::is_constant(name) {
+                    free.insert(name.clone());
+                }
+            }
+            Expr::Neg(a) | Expr::Factorial(a) => a.collect_free_variables(bound, free),
+            Expr::BinOp(_, a, b) | Expr::Interval(a, b) => {
+                a.collect_free_variables(bound, free);
+                b.collect_free_variables(bound, free);
+            }
+            Expr::List(items) => {
+                for item in items {
+                    item.collect_free_variables(bound, free);
+                }
+            }
+            Expr::Let(name, value, body) => {
+                value.collect_free_variables(bound, free);
+                let mut bound = bound.to_vec();
+                bound.push(name);
+                body.collect_free_variables(&bound, free);
+            }
+            Expr::Call(name, args) => {
+                // the special forms of `call` bind variables in their first argument
+                let (scoped, locals, rest): (Option<&Expr>, Vec<&str>, &[Expr]) =
+                    match (name.as_str(), args.as_slice()) {
+                        ("diff", _) => return,
+                        ("odesolve", [rhs, rest @ ..]) => (Some(rhs), vec!["t", "y"], rest),
+                        (
+                            "solve" | "integrate" | "sum" | "prod" | "minimize" | "maximize"
+                            | "poly",
+                            [f, Expr::Var(var), rest @ ..],
+                        ) => (Some(f), vec![var.as_str()], rest),
+                        ("minimize" | "maximize", [f, Expr::List(names), rest @ ..]) => (
+                            Some(f),
+                            names
+                                .iter()
+                                .filter_map(|name| match name {
+                                    Expr::Var(name) => Some(name.as_str()),
+                                    _ => None,
+                                })
+                                .collect(),
+                            rest,
+                        ),
+                        _ => (None, Vec::new(), args),
+                    };
+                if let Some(scoped) = scoped {
+                    let mut bound = bound.to_vec();
+                    bound.extend(locals);
+                    scoped.collect_free_variables(&bound, free);
+                }
+                for arg in rest {
+                    arg.collect_free_variables(bound, free);
+                }
+            }
+        }
+    }
+
+    /// Collects the names of all functions the expression calls.
+    pub(crate) fn collect_calls(&self, calls: &mut BTreeSet<String>) {
+        match self {
+            Expr::Value(Type::Function(function)) => function.body.collect_calls(calls),
+            Expr::Value(_) | Expr::Var(_) => (),
+            Expr::Neg(a) | Expr::Factorial(a) => a.collect_calls(calls),
+            Expr::BinOp(_, a, b) | Expr::Interval(a, b) | Expr::Let(_, a, b) => {
+                a.collect_calls(calls);
+                b.collect_calls(calls);
+            }
+            Expr::List(items) => items.iter().for_each(|item| item.collect_calls(calls)),
+            Expr::Call(name, args) => {
+                calls.insert(name.clone());
+                args.iter().for_each(|arg| arg.collect_calls(calls));
+            }
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Value(Type::Measurement(_)) => Op::PlusMinus.precedence(),
+            Expr::Value(value) if value.as_f64().is_some_and(|v| v.is_sign_negative()) => 3,
+            Expr::Neg(_) => 3,
+            Expr::BinOp(op, _, _) => op.precedence(),
+            Expr::Let(_, _, _) => 0,
+            _ => 6,
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = String;
+
+    fn from_str(equation: &str) -> Result<Expr, String> {
+        Expr::parse(equation)
+    }
+}
+
+/// Evaluates built-in functions, passing the unevaluated arguments to those that need them.
+fn call(name: &str, args: &[Expr], vars: Option<&dyn Resolver>) -> Result<Type, String> {
+    if let Some(Type::Function(function)) = vars.and_then(|vars| vars.get(name)) {
+        let args = args
+            .iter()
+            .map(|arg| arg.eval(vars))
+            .collect::<Result<Vec<Type>, String>>()?;
+        return function
+            .call(args, vars)
+            .map_err(|err| format!("{}: {}", name, err));
+    }
+    match name {
+        // cells of sheets without a value are 0, see `Sheet`
+        "CELL" => {
+            let [cell] = functions::expect_args(name, args.iter().collect())?;
+            let cell = var_arg(name, cell)?;
+            Ok(vars.and_then(|vars| vars.get(cell)).unwrap_or(Type::Int(0)))
+        }
+        "diff" => {
+            let [f, var] = functions::expect_args(name, args.iter().collect())?;
+            let var = var_arg(name, var)?;
+            Ok(Type::Function(Function::new(
+                vec![var.to_string()],
+                f.derivative(var)?,
+            )))
+        }
+        "solve" => {
+            let (equation, var, lo, hi) = match args {
+                [equation, var] => (
+                    equation,
+                    var,
+                    solve::DEFAULT_RANGE.0,
+                    solve::DEFAULT_RANGE.1,
+                ),
+                [equation, var, lo, hi] => {
+                    (equation, var, number_arg(lo, vars)?, number_arg(hi, vars)?)
+                }
+                _ => {
+                    return Err(format!(
+                        "{} expects 2 or 4 argument(s) but got {}",
+                        name,
+                        args.len()
+                    ))
+                }
+            };
+            let roots = solve::solve(equation, var_arg(name, var)?, lo, hi, vars)?;
+            Ok(Type::List(roots.into_iter().map(Type::Float).collect()))
+        }
+        "integrate" => {
+            let [f, var, a, b] = functions::expect_args(name, args.iter().collect())?;
+            let (a, b) = (number_arg(a, vars)?, number_arg(b, vars)?);
+            Ok(Type::Float(integrate::integrate(
+                f,
+                var_arg(name, var)?,
+                a,
+                b,
+                vars,
+            )?))
+        }
+        "sum" | "prod" => {
+            let [f, var, from, to] = functions::expect_args(name, args.iter().collect())?;
+            let (op, empty) = match name {
+                "sum" => (Op::Add, Type::Int(0)),
+                _ => (Op::Mul, Type::Int(1)),
+            };
+            let (from, to) = (int_arg(from, vars)?, int_arg(to, vars)?);
+            integrate::series(op, empty, f, var_arg(name, var)?, from, to, vars)
+        }
+        "minimize" | "maximize" => {
+            let maximize = name == "maximize";
+            match args {
+                [f, Expr::List(names), start] => {
+                    let names = names
+                        .iter()
+                        .map(|var| var_arg(name, var))
+                        .collect::<Result<Vec<&str>, String>>()?;
+                    let start = match start.eval(vars)? {
+                        Type::List(values) => values
+                            .into_iter()
+                            .map(|value| {
+                                value.as_f64().ok_or(format!(
+                                    "Expected a number but got {}",
+                                    value.get_type()
+                                ))
+                            })
+                            .collect::<Result<Vec<f64>, String>>()?,
+                        value => {
+                            return Err(format!(
+                                "Expected a list of numbers but got {}",
+                                value.get_type()
+                            ))
+                        }
+                    };
+                    let (point, value) =
+                        optimize::optimize_multi(f, &names, &start, maximize, vars)?;
+                    Ok(Type::List(vec![
+                        Type::List(point.into_iter().map(Type::Float).collect()),
+                        Type::Float(value),
+                    ]))
+                }
+                [f, var, lo, hi] => {
+                    let (lo, hi) = (number_arg(lo, vars)?, number_arg(hi, vars)?);
+                    let (x, value) =
+                        optimize::optimize(f, var_arg(name, var)?, lo, hi, maximize, vars)?;
+                    Ok(Type::List(vec![Type::Float(x), Type::Float(value)]))
+                }
+                _ => Err(format!(
+                    "{} expects (expr, x, lo, hi) or (expr, [x, y], [x0, y0]) but got {} argument(s)",
+                    name,
+                    args.len()
+                )),
+            }
+        }
+        "odesolve" => {
+            let (rhs, y0, t0, t1, steps) = match args {
+                [rhs, y0, t0, t1] => (rhs, y0, t0, t1, None),
+                [rhs, y0, t0, t1, steps] => (rhs, y0, t0, t1, Some(int_arg(steps, vars)?)),
+                _ => {
+                    return Err(format!(
+                        "{} expects 4 or 5 argument(s) but got {}",
+                        name,
+                        args.len()
+                    ))
+                }
+            };
+            let (y0, t0, t1) = (
+                number_arg(y0, vars)?,
+                number_arg(t0, vars)?,
+                number_arg(t1, vars)?,
+            );
+            let samples = ode::odesolve(rhs, y0, t0, t1, steps, vars)?;
+            Ok(Type::List(
+                samples
+                    .into_iter()
+                    .map(|(t, y)| Type::List(vec![Type::Float(t), Type::Float(y)]))
+                    .collect(),
+            ))
+        }
+        "poly" => match args {
+            [coefficients] => match coefficients.eval(vars)? {
+                Type::List(values) => Ok(Type::Polynomial(Polynomial::new(
+                    values
+                        .into_iter()
+                        .map(|value| {
+                            value.as_f64().ok_or(format!(
+                                "Coefficients must be numbers, not {}",
+                                value.get_type()
+                            ))
+                        })
+                        .collect::<Result<Vec<f64>, String>>()?,
+                ))),
+                value => Err(format!(
+                    "{} expects a list of coefficients but got {}",
+                    name,
+                    value.get_type()
+                )),
+            },
+            [f, var] => {
+                // evaluating with the variable bound to `x` turns the arithmetic into polynomial arithmetic
+                let mut vars = Scope::new(vars);
+                vars.set(var_arg(name, var)?, Type::Polynomial(Polynomial::x()));
+                Ok(Type::Polynomial(functions::polynomial_arg(
+                    name,
+                    f.eval(Some(&vars))?,
+                )?))
+            }
+            _ => Err(format!(
+                "{} expects 1 or 2 argument(s) but got {}",
+                name,
+                args.len()
+            )),
+        },
+        _ => functions::call(
+            name,
+            args.iter()
+                .map(|arg| arg.eval(vars))
+                .collect::<Result<Vec<Type>, String>>()?,
+        ),
+    }
+}
+
+/// Returns the name of the variable a function like `diff` operates on.
+fn var_arg<'a>(name: &str, arg: &'a Expr) -> Result<&'a str, String> {
+    match arg {
+        Expr::Var(var) => Ok(var),
+        arg => Err(format!("{} expects a variable but got {}", name, arg)),
+    }
+}
+
+fn number_arg(arg: &Expr, vars: Option<&dyn Resolver>) -> Result<f64, String> {
+    let value = arg.eval(vars)?;
+    value
+        .as_f64()
+        .ok_or(format!("Expected a number but got {}", value.get_type()))
+}
+
+fn int_arg(arg: &Expr, vars: Option<&dyn Resolver>) -> Result<i32, String> {
+    match arg.eval(vars)? {
+        Type::Int(value) => Ok(value),
+        value => Err(format!("Expected an integer but got {}", value.get_type())),
+    }
+}
+
+/// Looks up a variable in `vars`, falling back to the built-in constants and finally to a string of its name.
+fn var_or_string(operand: &str, vars: Option<&dyn Resolver>) -> Type {
+    vars.and_then(|vars| vars.get(operand))
+        .or_else(|| constants::get(operand))
+        .unwrap_or_else(|| Type::from(operand))
+}
+
+/// Writes `expr`, wrapped in parentheses if its precedence is lower than `min_precedence`.
+fn write_operand(f: &mut fmt::Formatter, expr: &Expr, min_precedence: u8) -> fmt::Result {
+    if expr.precedence() < min_precedence {
+        write!(f, "({})", expr)
+    } else {
+        write!(f, "{}", expr)
+    }
+}
+
+fn write_list(f: &mut fmt::Formatter, items: &[Expr]) -> fmt::Result {
+    for (idx, item) in items.iter().enumerate() {
+        if idx != 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Expr {
+    /// Prints the expression with as few parentheses as possible,
+    /// such that parsing the output results in the same expression.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Value(Type::Float(value)) if value.is_finite() && value.fract() == 0.0 => {
+                write!(f, "{:.1}", value)
+            }
+            Expr::Value(Type::String(value)) => {
+                write!(
+                    f,
+                    "\"{}\"",
+                    value.replace('\\', "\\\\").replace('"', "\\\"")
+                )
+            }
+            Expr::Value(Type::Measurement(value)) => {
+                write!(f, "{} ± {}", value.value(), value.uncertainty())
+            }
+            Expr::Value(value) => write!(f, "{}", value),
+            Expr::Var(name) => write!(f, "{}", name),
+            Expr::Factorial(a) => {
+                write_operand(f, a, 6)?;
+                write!(f, "!")
+            }
+            Expr::Neg(a) => {
+                write!(f, "-")?;
+                write_operand(f, a, 3)
+            }
+            Expr::BinOp(op, a, b) => {
+                let precedence = op.precedence();
+                let (left, right) = match op {
+                    Op::Pow => (precedence + 1, 3),
+                    Op::PlusMinus => (6, 6),
+                    Op::Eq => (1, 1),
+                    _ => (precedence, precedence + 1),
+                };
+                write_operand(f, a, left)?;
+                write!(f, "{}", op.symbol())?;
+                write_operand(f, b, right)
+            }
+            Expr::Interval(lo, hi) => write!(f, "[{} .. {}]", lo, hi),
+            Expr::Call(name, args) => {
+                write!(f, "{}(", name)?;
+                write_list(f, args)?;
+                write!(f, ")")
+            }
+            Expr::List(items) => {
+                write!(f, "[")?;
+                write_list(f, items)?;
+                write!(f, "]")
+            }
+            Expr::Let(name, value, body) => match value.as_ref() {
+                Expr::Value(Type::Function(function)) => write!(
+                    f,
+                    "let {}({}) = {} in {}",
+                    name,
+                    function.params.join(", "),
+                    function.body,
+                    body
+                ),
+                value => write!(f, "let {} = {} in {}", name, value, body),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_round_trip() -> Result<(), String> {
+        for equation in [
+            "1 - (2 - 3)",
+            "2^3^2",
+            "(2^3)^2",
+            "-x^2",
+            "(-x)^2",
+            "2*-x + 1.0",
+            "a/(b*c)%3",
+            "sqrt(x ± 0.1)*[-1 .. 2]",
+            "f([1, x^2], [])",
+            "-(x + 1)!!^2",
+            "(let x = 1 in x)*2 + (let y = 2 in y^2)",
+            "let f(x, y) = x*y in f(2, 3)",
+            "env(\"HOME\") + \"1\"*x",
+            "\"say \\\"hi\\\" \\\\ - 1, 2\" + x",
+        ] {
+            let expr = Expr::parse(equation)?;
+            assert_eq!(expr.to_string(), equation);
+            assert_eq!(Expr::parse(&expr.to_string())?, expr);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,266 @@
+use crate::functions;
+use crate::type_enum::Type;
+
+const MAX_ITERATIONS: usize = 100;
+
+/// Calls the financial function `name`, returning `None` if there is no such function.
+///
+/// The functions follow the conventions of spreadsheets: money paid out is negative, money received positive,
+/// and the optional `type` argument is `1` for payments at the beginning of each period and `0` for payments at the end.
+pub(crate) fn call(name: &str, args: &[Type]) -> Option<Result<Type, String>> {
+    let arity = match name {
+        "pv" | "fv" | "pmt" | "nper" => 3..=5,
+        "rate" => 3..=6,
+        "npv" => 2..=2,
+        "irr" => 1..=2,
+        "effect" | "nominal" => 2..=2,
+        "compound" => 3..=4,
+        _ => return None,
+    };
+    Some(
+        functions::check_arity(name, args, arity)
+            .and_then(|()| apply(name, args))
+            .map(Type::Float),
+    )
+}
+
+fn apply(name: &str, args: &[Type]) -> Result<f64, String> {
+    match (name, args) {
+        ("npv", [rate, Type::List(flows)]) => {
+            return Ok(npv(number(name, rate)?, &numbers(name, flows)?))
+        }
+        ("irr", [Type::List(flows)]) => return irr(&numbers(name, flows)?, 0.1),
+        ("irr", [Type::List(flows), guess]) => {
+            return irr(&numbers(name, flows)?, number(name, guess)?)
+        }
+        ("npv", [_, flows]) | ("irr", [flows, ..]) => {
+            return Err(format!(
+                "{} expects a list of cash flows but got {}",
+                name,
+                flows.get_type()
+            ))
+        }
+        _ => (),
+    }
+    let args = numbers(name, args)?;
+    // optional arguments default to 0, except the guess for `rate`
+    let arg = |idx: usize| args.get(idx).copied().unwrap_or(0.0);
+    match name {
+        "pv" => pv(arg(0), arg(1), arg(2), arg(3), arg(4)),
+        "fv" => Ok(fv(arg(0), arg(1), arg(2), arg(3), arg(4))),
+        "pmt" => pmt(arg(0), arg(1), arg(2), arg(3), arg(4)),
+        "nper" => nper(arg(0), arg(1), arg(2), arg(3), arg(4)),
+        "rate" => rate(
+            arg(0),
+            arg(1),
+            arg(2),
+            arg(3),
+            arg(4),
+            args.get(5).copied().unwrap_or(0.1),
+        ),
+        "effect" => Ok((1.0 + arg(0) / periods(arg(1))?).powf(periods(arg(1))?) - 1.0),
+        "nominal" => {
+            let n = periods(arg(1))?;
+            Ok(n * ((1.0 + arg(0)).powf(1.0 / n) - 1.0))
+        }
+        "compound" => {
+            let n = if args.len() == 4 {
+                periods(arg(3))?
+            } else {
+                1.0
+            };
+            Ok(arg(0) * (1.0 + arg(1) / n).powf(n * arg(2)))
+        }
+        _ => Err(format!("Unknown function {}", name)),
+    }
+}
+
+fn number(name: &str, value: &Type) -> Result<f64, String> {
+    value.as_f64().ok_or(format!(
+        "{} expects numbers but got {}",
+        name,
+        value.get_type()
+    ))
+}
+
+fn numbers(name: &str, values: &[Type]) -> Result<Vec<f64>, String> {
+    values.iter().map(|value| number(name, value)).collect()
+}
+
+/// Checks the number of compounding periods per year.
+fn periods(n: f64) -> Result<f64, String> {
+    if n < 1.0 {
+        return Err(format!(
+            "The number of compounding periods must be at least 1, got {}",
+            n
+        ));
+    }
+    Ok(n.trunc())
+}
+
+/// Returns `(1 + rate)^nper` and the factor of the payments in the time value of money equation
+/// `pv * growth + pmt * annuity + fv = 0`.
+fn factors(rate: f64, nper: f64, due: f64) -> (f64, f64) {
+    if rate == 0.0 {
+        return (1.0, nper);
+    }
+    let growth = (1.0 + rate).powf(nper);
+    (growth, (1.0 + rate * due) * (growth - 1.0) / rate)
+}
+
+fn pv(rate: f64, nper: f64, pmt: f64, fv: f64, due: f64) -> Result<f64, String> {
+    let (growth, annuity) = factors(rate, nper, due);
+    if growth == 0.0 {
+        return Err("Unable to divide by zero".to_string());
+    }
+    Ok(-(fv + pmt * annuity) / growth)
+}
+
+fn fv(rate: f64, nper: f64, pmt: f64, pv: f64, due: f64) -> f64 {
+    let (growth, annuity) = factors(rate, nper, due);
+    -(pv * growth + pmt * annuity)
+}
+
+fn pmt(rate: f64, nper: f64, pv: f64, fv: f64, due: f64) -> Result<f64, String> {
+    let (growth, annuity) = factors(rate, nper, due);
+    if annuity == 0.0 {
+        return Err("Unable to divide by zero".to_string());
+    }
+    Ok(-(fv + pv * growth) / annuity)
+}
+
+fn nper(rate: f64, pmt: f64, pv: f64, fv: f64, due: f64) -> Result<f64, String> {
+    if rate == 0.0 {
+        if pmt == 0.0 {
+            return Err("Unable to divide by zero".to_string());
+        }
+        return Ok(-(pv + fv) / pmt);
+    }
+    let payment = pmt * (1.0 + rate * due) / rate;
+    let ratio = (payment - fv) / (payment + pv);
+    if ratio <= 0.0 || !ratio.is_finite() || rate <= -1.0 {
+        return Err("The payments never pay off the present value".to_string());
+    }
+    Ok(ratio.ln() / rate.ln_1p())
+}
+
+/// Solves the time value of money equation for the rate with Newton's method.
+fn rate(nper: f64, pmt: f64, pv: f64, fv: f64, due: f64, guess: f64) -> Result<f64, String> {
+    let f = |rate: f64| {
+        let (growth, annuity) = factors(rate, nper, due);
+        pv * growth + pmt * annuity + fv
+    };
+    newton(f, guess).ok_or("rate did not converge, try a different guess".to_string())
+}
+
+/// Returns the net present value of cash flows at the end of each period, starting one period from now.
+fn npv(rate: f64, flows: &[f64]) -> f64 {
+    flows
+        .iter()
+        .rev()
+        .fold(0.0, |acc, flow| (acc + flow) / (1.0 + rate))
+}
+
+/// Returns the internal rate of return, the rate at which the net present value of the cash flows is zero.
+fn irr(flows: &[f64], guess: f64) -> Result<f64, String> {
+    if !flows.iter().any(|flow| *flow > 0.0) || !flows.iter().any(|flow| *flow < 0.0) {
+        return Err("irr needs at least one positive and one negative cash flow".to_string());
+    }
+    // the first cash flow happens now, unlike for npv
+    let f = |rate: f64| npv(rate, flows) * (1.0 + rate);
+    newton(f, guess).ok_or("irr did not converge, try a different guess".to_string())
+}
+
+/// Finds a root of `f` near `guess` with Newton's method, approximating the derivative numerically.
+fn newton(f: impl Fn(f64) -> f64, guess: f64) -> Option<f64> {
+    let mut x = guess;
+    for _ in 0..MAX_ITERATIONS {
+        let fx = f(x);
+        let h = 1e-7 * (1.0 + x.abs());
+        let step = fx * 2.0 * h / (f(x + h) - f(x - h));
+        if !step.is_finite() {
+            return None;
+        }
+        x -= step;
+        if x <= -1.0 {
+            // rates below -100% are meaningless, move back into the valid range
+            x = (x + step - 1.0) / 2.0;
+        }
+        if step.abs() <= 1e-12 * (1.0 + x.abs()) {
+            return Some(x);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Calls `name` with float arguments, as the financial functions always result in a float.
+    fn call_floats(name: &str, args: &[f64]) -> Result<f64, String> {
+        let args = args.iter().map(|arg| Type::Float(*arg)).collect();
+        match functions::call(name, args)? {
+            Type::Float(value) => Ok(value),
+            value => Err(format!("Expected f64 but got {}", value.get_type())),
+        }
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= 1e-8 * (1.0 + b.abs()), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn time_value_of_money() -> Result<(), String> {
+        // a loan of 200000 over 30 years at 6% per year, paid monthly
+        let payment = call_floats("pmt", &[0.005, 360.0, 200_000.0])?;
+        assert_close(payment, -1199.101050304);
+        assert_close(call_floats("pv", &[0.005, 360.0, payment])?, 200_000.0);
+        assert_close(call_floats("fv", &[0.005, 360.0, payment, 200_000.0])?, 0.0);
+        assert_close(call_floats("nper", &[0.005, payment, 200_000.0])?, 360.0);
+        assert_close(call_floats("rate", &[360.0, payment, 200_000.0])?, 0.005);
+
+        assert_close(call_floats("fv", &[0.0, 10.0, -100.0])?, 1000.0);
+        assert_close(call_floats("fv", &[0.1, 2.0, -100.0, 0.0, 1.0])?, 231.0);
+        assert!(call_floats("nper", &[0.1, -1.0, 1000.0]).is_err());
+        assert_eq!(
+            call_floats("pmt", &[0.1]),
+            Err("pmt expects 3 to 5 argument(s) but got 1".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cash_flows() -> Result<(), String> {
+        let flows = Type::List(
+            [-100.0, 39.0, 59.0, 55.0, 20.0]
+                .into_iter()
+                .map(Type::Float)
+                .collect(),
+        );
+        let Some(Ok(Type::Float(irr))) = call("irr", std::slice::from_ref(&flows)) else {
+            panic!("irr should converge");
+        };
+        assert_close(irr, 0.2809484211599611);
+        let Some(Ok(Type::Float(npv))) = call("npv", &[Type::Float(irr), flows]) else {
+            panic!("npv should result in a float");
+        };
+        assert_close(npv, 0.0);
+
+        let positive = Type::List(vec![Type::Int(1), Type::Int(2)]);
+        assert!(call("irr", &[positive]).is_some_and(|res| res.is_err()));
+        Ok(())
+    }
+
+    #[test]
+    fn compound_interest() -> Result<(), String> {
+        assert_close(call_floats("effect", &[0.12, 12.0])?, 0.12682503013196977);
+        assert_close(call_floats("nominal", &[0.12682503013196977, 12.0])?, 0.12);
+        assert_close(
+            call_floats("compound", &[1000.0, 0.05, 10.0])?,
+            1628.894626777442,
+        );
+        assert!(call_floats("effect", &[0.12, 0.0]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+use std::ops::RangeInclusive;
+
+use crate::combinatorics;
+use crate::environment;
+use crate::finance;
+use crate::number_theory;
+use crate::polynomial::Polynomial;
+use crate::random;
+use crate::sheet;
+use crate::statistics;
+use crate::type_enum::Type;
+
+/// The mathematical functions of a single argument that can be called in equations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum MathFn {
+    Abs,
+    Sqrt,
+    Exp,
+    Ln,
+    Log10,
+    Sin,
+    Cos,
+    Tan,
+}
+
+impl MathFn {
+    pub(crate) fn from_name(name: &str) -> Option<MathFn> {
+        match name {
+            "abs" => Some(MathFn::Abs),
+            "sqrt" => Some(MathFn::Sqrt),
+            "exp" => Some(MathFn::Exp),
+            "ln" => Some(MathFn::Ln),
+            "log10" => Some(MathFn::Log10),
+            "sin" => Some(MathFn::Sin),
+            "cos" => Some(MathFn::Cos),
+            "tan" => Some(MathFn::Tan),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            MathFn::Abs => "abs",
+            MathFn::Sqrt => "sqrt",
+            MathFn::Exp => "exp",
+            MathFn::Ln => "ln",
+            MathFn::Log10 => "log10",
+            MathFn::Sin => "sin",
+            MathFn::Cos => "cos",
+            MathFn::Tan => "tan",
+        }
+    }
+
+    pub(crate) fn apply_f64(self, x: f64) -> Result<f64, String> {
+        match self {
+            MathFn::Abs => Ok(x.abs()),
+            MathFn::Sqrt => {
+                if x < 0.0 {
+                    Err("Unable to calculate the square root of a negative number".to_string())
+                } else {
+                    Ok(x.sqrt())
+                }
+            }
+            MathFn::Exp => Ok(x.exp()),
+            MathFn::Ln | MathFn::Log10 => {
+                if x <= 0.0 {
+                    Err("Unable to calculate the logarithm of a non-positive number".to_string())
+                } else if self == MathFn::Ln {
+                    Ok(x.ln())
+                } else {
+                    Ok(x.log10())
+                }
+            }
+            MathFn::Sin => Ok(x.sin()),
+            MathFn::Cos => Ok(x.cos()),
+            MathFn::Tan => Ok(x.tan()),
+        }
+    }
+
+    /// Returns the derivative of the function at `x`, used for propagating uncertainties.
+    pub(crate) fn derivative_f64(self, x: f64) -> f64 {
+        match self {
+            MathFn::Abs => x.signum(),
+            MathFn::Sqrt => 0.5 / x.sqrt(),
+            MathFn::Exp => x.exp(),
+            MathFn::Ln => 1.0 / x,
+            MathFn::Log10 => 1.0 / (x * std::f64::consts::LN_10),
+            MathFn::Sin => x.cos(),
+            MathFn::Cos => -x.sin(),
+            MathFn::Tan => 1.0 / (x.cos() * x.cos()),
+        }
+    }
+
+    fn apply(self, x: Type) -> Result<Type, String> {
+        match x {
+            Type::Int(a) if self == MathFn::Abs => Ok(a
+                .checked_abs()
+                .map_or(Type::Float((a as f64).abs()), Type::Int)),
+            Type::Int(a) => Ok(Type::Float(self.apply_f64(a as f64)?)),
+            Type::Float(a) => Ok(Type::Float(self.apply_f64(a)?)),
+            Type::Interval(a) => Ok(Type::Interval(a.apply(self)?)),
+            Type::Measurement(a) => Ok(Type::Measurement(a.apply(self)?)),
+            x => Err(format!(
+                "Unable to calculate {} of {}",
+                self.name(),
+                x.get_type()
+            )),
+        }
+    }
+}
+
+/// Calls the built-in function `name` with already evaluated arguments.
+pub(crate) fn call(name: &str, args: Vec<Type>) -> Result<Type, String> {
+    if let Some(f) = MathFn::from_name(name) {
+        let [x] = expect_args(name, args)?;
+        return f.apply(x);
+    }
+    if let Some(result) = number_theory::call(name, &args) {
+        return result;
+    }
+    if let Some(result) = combinatorics::call(name, &args) {
+        return result;
+    }
+    if let Some(result) = statistics::call(name, &args) {
+        return result;
+    }
+    if let Some(result) = random::call(name, &args) {
+        return result;
+    }
+    if let Some(result) = finance::call(name, &args) {
+        return result;
+    }
+    if let Some(result) = sheet::call(name, &args) {
+        return result;
+    }
+    if let Some(result) = environment::call(name, &args) {
+        return result;
+    }
+    match name {
+        "polyval" => {
+            let [p, x] = expect_args(name, args)?;
+            // Horner's method on values keeps e.g. intervals and measurements intact
+            let p = polynomial_arg(name, p)?;
+            p.coefficients()
+                .into_iter()
+                .try_fold(Type::Int(0), |acc, c| (acc * x.clone())? + Type::Float(c))
+        }
+        "polyder" => {
+            let [p] = expect_args(name, args)?;
+            Ok(Type::Polynomial(polynomial_arg(name, p)?.derivative()))
+        }
+        "roots" => {
+            let [p] = expect_args(name, args)?;
+            Ok(Type::List(
+                polynomial_arg(name, p)?
+                    .roots()?
+                    .into_iter()
+                    .map(|(re, im)| Type::List(vec![Type::Float(re), Type::Float(im)]))
+                    .collect(),
+            ))
+        }
+        _ => Err(format!("Unknown function {}", name)),
+    }
+}
+
+/// Converts an argument of the function `name` into a polynomial, treating numbers as constant polynomials.
+pub(crate) fn polynomial_arg(name: &str, arg: Type) -> Result<Polynomial, String> {
+    match arg {
+        Type::Polynomial(p) => Ok(p),
+        Type::Int(_) | Type::Float(_) => Ok(Polynomial::constant(arg.as_f64().unwrap_or(0.0))),
+        arg => Err(format!(
+            "{} expects a polynomial but got {}",
+            name,
+            arg.get_type()
+        )),
+    }
+}
+
+/// Checks that the number of arguments passed to the function `name` is within `arity`.
+pub(crate) fn check_arity(
+    name: &str,
+    args: &[Type],
+    arity: RangeInclusive<usize>,
+) -> Result<(), String> {
+    if arity.contains(&args.len()) {
+        return Ok(());
+    }
+    let expected = match arity.end() - arity.start() {
+        0 => arity.start().to_string(),
+        1 => format!("{} or {}", arity.start(), arity.end()),
+        _ => format!("{} to {}", arity.start(), arity.end()),
+    };
+    Err(format!(
+        "{} expects {} argument(s) but got {}",
+        name,
+        expected,
+        args.len()
+    ))
+}
+
+/// Checks that exactly `N` arguments were passed to the function `name`.
+pub(crate) fn expect_args<T, const N: usize>(name: &str, args: Vec<T>) -> Result<[T; N], String> {
+    args.try_into().map_err(|args: Vec<T>| {
+        format!("{} expects {} argument(s) but got {}", name, N, args.len())
+    })
+}
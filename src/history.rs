@@ -0,0 +1,229 @@
+use std::collections::VecDeque;
+
+use crate::expr::Expr;
+use crate::session::Session;
+use crate::type_enum::Type;
+
+/// The maximum number of changes kept for undoing them, older changes are forgotten.
+const MAX_CHANGES: usize = 1000;
+
+/// The state of a variable, `None` if it has no value or formula.
+#[derive(Debug, Clone)]
+struct Variable {
+    value: Option<Type>,
+    formula: Option<Expr>,
+    read_only: bool,
+}
+
+impl Variable {
+    fn of(session: &Session, name: &str) -> Variable {
+        Variable {
+            value: session.workspace().values().get(name).cloned(),
+            formula: session.workspace().formula(name).cloned(),
+            read_only: session.is_marked_read_only(name),
+        }
+    }
+}
+
+/// A change of the variables of a session by an input.
+///
+/// Only the states of the changed variables before and after the change are kept.
+#[derive(Debug, Clone)]
+pub struct Change {
+    input: String,
+    changed: Vec<String>,
+    before: Vec<Variable>,
+    after: Vec<Variable>,
+}
+
+impl Change {
+    /// Returns the input that made the change.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// Returns the sorted names of the changed variables.
+    pub fn changed(&self) -> &[String] {
+        &self.changed
+    }
+
+    /// Returns the changed variables with their values after the change, `None` if they have no value.
+    pub fn after(&self) -> impl Iterator<Item = (&str, Option<&Type>)> {
+        self.changed
+            .iter()
+            .zip(&self.after)
+            .map(|(name, variable)| (name.as_str(), variable.value.as_ref()))
+    }
+
+    /// Restores the changed variables of `session` to `states`.
+    ///
+    /// All changed variables are restored exactly and the others are the same before and after the change,
+    /// so nothing needs to be recomputed.
+    fn restore(&self, session: &mut Session, states: &[Variable]) {
+        for (name, variable) in self.changed.iter().zip(states) {
+            session.restore(
+                name,
+                variable.value.clone(),
+                variable.formula.clone(),
+                variable.read_only,
+            );
+        }
+    }
+}
+
+/// The history of the changes made to a session, which can be undone and redone.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::{history::History, session::Session, type_enum::Type};
+///
+/// let mut session = Session::new();
+/// let mut history = History::new();
+///
+/// let before = session.clone();
+/// session.workspace_mut().set("x", Type::Int(1)).unwrap();
+/// history.record("let x = 1", before, &session);
+///
+/// let before = session.clone();
+/// session.workspace_mut().set("x", Type::Int(100)).unwrap();
+/// history.record("let x = 100", before, &session);
+///
+/// assert_eq!(history.undo(&mut session).map(|change| change.input()), Some("let x = 100"));
+/// assert_eq!(session.workspace().values()["x"], Type::Int(1));
+/// assert!(history.redo(&mut session).is_some());
+/// assert_eq!(session.workspace().values()["x"], Type::Int(100));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    done: VecDeque<Change>,
+    undone: Vec<Change>,
+}
+
+impl History {
+    /// Creates an empty history.
+    pub fn new() -> History {
+        History::default()
+    }
+
+    /// Records the change `input` made from the session `before` to the session `after`.
+    ///
+    /// Returns `false` without recording anything if no variable changed.
+    /// Otherwise the changes that were undone can no longer be redone.
+    pub fn record(&mut self, input: &str, before: Session, after: &Session) -> bool {
+        let changed = after.changed_variables(&before);
+        if changed.is_empty() {
+            return false;
+        }
+        if self.done.len() == MAX_CHANGES {
+            self.done.pop_front();
+        }
+        self.done.push_back(Change {
+            input: input.to_string(),
+            before: changed
+                .iter()
+                .map(|name| Variable::of(&before, name))
+                .collect(),
+            after: changed
+                .iter()
+                .map(|name| Variable::of(after, name))
+                .collect(),
+            changed,
+        });
+        self.undone.clear();
+        true
+    }
+
+    /// Reverts `session` to before the last change, returning the undone change or `None` if there is nothing to undo.
+    pub fn undo(&mut self, session: &mut Session) -> Option<&Change> {
+        let change = self.done.pop_back()?;
+        change.restore(session, &change.before);
+        self.undone.push(change);
+        self.undone.last()
+    }
+
+    /// Applies the last undone change to `session` again, returning it or `None` if there is nothing to redo.
+    pub fn redo(&mut self, session: &mut Session) -> Option<&Change> {
+        let change = self.undone.pop()?;
+        change.restore(session, &change.after);
+        self.done.push_back(change);
+        self.done.back()
+    }
+
+    /// Returns the changes that were not undone, the oldest first.
+    pub fn changes(&self) -> impl Iterator<Item = &Change> {
+        self.done.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(history: &mut History, session: &mut Session, name: &str, value: i32) {
+        let before = session.clone();
+        session.workspace_mut().set(name, Type::Int(value)).unwrap();
+        history.record(&format!("let {} = {}", name, value), before, session);
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut session = Session::new();
+        let mut history = History::new();
+        set(&mut history, &mut session, "a", 1);
+        set(&mut history, &mut session, "a", 1);
+        set(&mut history, &mut session, "b", 2);
+        assert_eq!(history.changes().count(), 2);
+
+        assert!(history.undo(&mut session).is_some());
+        assert!(history.undo(&mut session).is_some());
+        assert!(history.undo(&mut session).is_none());
+        assert!(session.workspace().values().is_empty());
+
+        assert_eq!(
+            history.redo(&mut session).map(|change| change.changed()),
+            Some(&["a".to_string()][..])
+        );
+        set(&mut history, &mut session, "c", 3);
+        assert!(history.redo(&mut session).is_none());
+        assert_eq!(
+            history.changes().map(Change::input).collect::<Vec<&str>>(),
+            ["let a = 1", "let c = 3"]
+        );
+    }
+
+    #[test]
+    fn restores_formulas_exactly() -> Result<(), String> {
+        let mut session = Session::new();
+        let mut history = History::new();
+        session.workspace_mut().define("r", "rand() + b")?;
+        session.workspace_mut().define("b", "a * 2")?;
+        session.workspace_mut().set("a", Type::Int(1))?;
+        let original = session.clone();
+
+        // swap the formula around, which creates a cycle if it is restored one variable at a time
+        let before = session.clone();
+        session.workspace_mut().set("b", Type::Int(5))?;
+        session.workspace_mut().define("a", "b - 1")?;
+        session.set_read_only("a");
+        history.record("swap", before, &session);
+        let swapped = session.clone();
+
+        history.undo(&mut session);
+        assert!(session.changed_variables(&original).is_empty());
+        history.redo(&mut session);
+        assert!(session.changed_variables(&swapped).is_empty());
+        assert_eq!(
+            history
+                .changes()
+                .next()
+                .map(|change| change.after().collect::<Vec<_>>()),
+            Some(vec![
+                ("a", Some(&Type::Int(4))),
+                ("b", Some(&Type::Int(5))),
+                ("r", swapped.workspace().values().get("r"))
+            ])
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,231 @@
+use crate::expr::{Expr, Op};
+use crate::numeric::RealFunction;
+use crate::resolver::{Resolver, Scope};
+use crate::type_enum::Type;
+
+/// The abscissae of the 15-point Gauss–Kronrod rule, the odd ones are shared with the 7-point Gauss rule.
+const KRONROD_NODES: [f64; 8] = [
+    0.991_455_371_120_812_6,
+    0.949_107_912_342_758_5,
+    0.864_864_423_359_769_1,
+    0.741_531_185_599_394_4,
+    0.586_087_235_467_691_1,
+    0.405_845_151_377_397_2,
+    0.207_784_955_007_898_5,
+    0.0,
+];
+const KRONROD_WEIGHTS: [f64; 8] = [
+    0.022_935_322_010_529_22,
+    0.063_092_092_629_978_55,
+    0.104_790_010_322_250_18,
+    0.140_653_259_715_525_92,
+    0.169_004_726_639_267_9,
+    0.190_350_578_064_785_4,
+    0.204_432_940_075_298_9,
+    0.209_482_141_084_727_83,
+];
+const GAUSS_WEIGHTS: [f64; 4] = [
+    0.129_484_966_168_869_7,
+    0.279_705_391_489_276_7,
+    0.381_830_050_505_118_9,
+    0.417_959_183_673_469_4,
+];
+
+const MAX_SEGMENTS: usize = 2_000;
+const MAX_TERMS: i64 = 1_000_000;
+
+/// Integrates `expr` over `var` from `a` to `b` with adaptive Gauss–Kronrod quadrature.
+///
+/// The segment with the largest error estimate is bisected until the estimated error is small enough.
+/// Infinite bounds are mapped onto finite ones by a change of variables.
+pub(crate) fn integrate(
+    expr: &Expr,
+    var: &str,
+    a: f64,
+    b: f64,
+    vars: Option<&dyn Resolver>,
+) -> Result<f64, String> {
+    if a.is_nan() || b.is_nan() {
+        return Err("The bounds of an integral must be numbers".to_string());
+    }
+    if a == b {
+        return Ok(0.0);
+    }
+    if a > b {
+        return Ok(-integrate(expr, var, b, a, vars)?);
+    }
+    let mut f = RealFunction::new(expr, var, vars);
+    let integral = match (a.is_finite(), b.is_finite()) {
+        (true, true) => quadrature(|x| f.eval(x), a, b),
+        // x = a + t / (1 - t)
+        (true, false) => quadrature(
+            |t| Ok(f.eval(a + t / (1.0 - t))? / ((1.0 - t) * (1.0 - t))),
+            0.0,
+            1.0,
+        ),
+        // x = b - (1 - t) / t
+        (false, true) => quadrature(|t| Ok(f.eval(b - (1.0 - t) / t)? / (t * t)), 0.0, 1.0),
+        // x = t / (1 - t^2)
+        (false, false) => quadrature(
+            |t| {
+                let s = 1.0 - t * t;
+                Ok(f.eval(t / s)? * (1.0 + t * t) / (s * s))
+            },
+            -1.0,
+            1.0,
+        ),
+    };
+    integral.map_err(|err| match err {
+        Some(err) => err,
+        None => format!(
+            "The integral of {} from {} to {} did not converge",
+            expr, a, b
+        ),
+    })
+}
+
+/// Integrates `f` over the finite range `[a .. b]`, returning `None` as the error if the integral did not converge.
+fn quadrature(
+    mut f: impl FnMut(f64) -> Result<f64, String>,
+    a: f64,
+    b: f64,
+) -> Result<f64, Option<String>> {
+    // (a, b, integral, error)
+    let mut segments = vec![gauss_kronrod(&mut f, a, b).map_err(Some)?];
+    loop {
+        let integral: f64 = segments.iter().map(|s| s.2).sum();
+        let error: f64 = segments.iter().map(|s| s.3).sum();
+        if !integral.is_finite() || !error.is_finite() {
+            return Err(None);
+        }
+        if error <= 1e-12_f64.max(1e-10 * integral.abs()) {
+            return Ok(integral);
+        }
+        if segments.len() >= MAX_SEGMENTS {
+            return Err(None);
+        }
+        let idx = segments
+            .iter()
+            .enumerate()
+            .max_by(|(_, x), (_, y)| x.3.total_cmp(&y.3))
+            .map_or(0, |(idx, _)| idx);
+        let (lo, hi, _, _) = segments.swap_remove(idx);
+        let mid = lo / 2.0 + hi / 2.0;
+        if mid <= lo || mid >= hi {
+            // the segment can't be split any further
+            return Err(None);
+        }
+        segments.push(gauss_kronrod(&mut f, lo, mid).map_err(Some)?);
+        segments.push(gauss_kronrod(&mut f, mid, hi).map_err(Some)?);
+    }
+}
+
+/// Returns `(a, b, integral, error)` of `f` over `[a .. b]` using the 7-point Gauss and 15-point Kronrod rules.
+fn gauss_kronrod(
+    f: &mut impl FnMut(f64) -> Result<f64, String>,
+    a: f64,
+    b: f64,
+) -> Result<(f64, f64, f64, f64), String> {
+    let center = a / 2.0 + b / 2.0;
+    let half_width = b / 2.0 - a / 2.0;
+    let mut kronrod = 0.0;
+    let mut gauss = 0.0;
+    for (idx, (node, weight)) in KRONROD_NODES.iter().zip(KRONROD_WEIGHTS).enumerate() {
+        let values = if *node == 0.0 {
+            f(center)?
+        } else {
+            f(center - half_width * node)? + f(center + half_width * node)?
+        };
+        kronrod += weight * values;
+        if idx % 2 == 1 {
+            gauss += GAUSS_WEIGHTS[idx / 2] * values;
+        }
+    }
+    Ok((
+        a,
+        b,
+        kronrod * half_width,
+        ((kronrod - gauss) * half_width).abs(),
+    ))
+}
+
+/// Combines the values of `expr` for every integer `var` from `from` to `to` with `op`,
+/// e.g. the sum for `Op::Add`, resulting in `empty` if there are no terms.
+pub(crate) fn series(
+    op: Op,
+    empty: Type,
+    expr: &Expr,
+    var: &str,
+    from: i32,
+    to: i32,
+    vars: Option<&dyn Resolver>,
+) -> Result<Type, String> {
+    if to as i64 - from as i64 >= MAX_TERMS {
+        return Err(format!(
+            "Unable to combine more than {} terms of {}",
+            MAX_TERMS, expr
+        ));
+    }
+    let mut vars = Scope::new(vars);
+    let mut result = None;
+    for i in from..=to {
+        vars.set(var, Type::Int(i));
+        let term = expr.eval(Some(&vars))?;
+        result = Some(match result {
+            Some(result) => op.apply(result, term)?,
+            None => term,
+        });
+    }
+    Ok(result.unwrap_or(empty))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn integrate_str(equation: &str, a: f64, b: f64) -> Result<f64, String> {
+        integrate(&Expr::parse(equation)?, "x", a, b, None)
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= 1e-9 * (1.0 + b.abs()), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn finite_bounds() -> Result<(), String> {
+        assert_close(integrate_str("x^2", 0.0, 3.0)?, 9.0);
+        assert_close(integrate_str("sin(x)", 0.0, std::f64::consts::PI)?, 2.0);
+        assert_close(integrate_str("x", 2.0, 0.0)?, -2.0);
+        assert_close(integrate_str("1 / sqrt(x)", 0.0, 1.0)?, 2.0);
+        Ok(())
+    }
+
+    #[test]
+    fn infinite_bounds() -> Result<(), String> {
+        assert_close(integrate_str("exp(-x)", 0.0, f64::INFINITY)?, 1.0);
+        assert_close(
+            integrate_str("exp(-x^2)", f64::NEG_INFINITY, f64::INFINITY)?,
+            std::f64::consts::PI.sqrt(),
+        );
+        assert!(integrate_str("1 / x", 1.0, f64::INFINITY).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn sums_and_products() -> Result<(), String> {
+        let expr = Expr::parse("i^2")?;
+        assert_eq!(
+            series(Op::Add, Type::Int(0), &expr, "i", 1, 10, None)?,
+            Type::Int(385)
+        );
+        assert_eq!(
+            series(Op::Mul, Type::Int(1), &Expr::parse("i")?, "i", 1, 5, None)?,
+            Type::Int(120)
+        );
+        assert_eq!(
+            series(Op::Add, Type::Int(0), &expr, "i", 1, 0, None)?,
+            Type::Int(0)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,363 @@
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::functions::MathFn;
+
+/// Represents a closed interval `[lo .. hi]` of real numbers.
+///
+/// Every operation on intervals rounds outward, so the resulting interval is guaranteed
+/// to enclose all values the operation could produce for members of its operands.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::interval::Interval;
+///
+/// let a = Interval::new(1.9, 2.1).unwrap();
+/// let b = Interval::new(-1.0, 1.0).unwrap();
+///
+/// assert_eq!(a * b, Interval::new(-2.1, 2.1).unwrap());
+/// assert!((a / b).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Interval {
+    lo: f64,
+    hi: f64,
+}
+
+impl Interval {
+    /// Creates the interval `[lo .. hi]`.
+    ///
+    /// Returns an error if `lo` is greater than `hi` or if one of the bounds is `NaN`.
+    pub fn new(lo: f64, hi: f64) -> Result<Interval, String> {
+        if lo.is_nan() || hi.is_nan() {
+            return Err("Interval bounds must be numbers".to_string());
+        }
+        if lo > hi {
+            return Err(format!(
+                "Lower bound {} of an interval must not be greater than upper bound {}",
+                lo, hi
+            ));
+        }
+        Ok(Interval { lo, hi })
+    }
+
+    /// Creates the degenerate interval `[x .. x]`, containing only `x`.
+    pub fn point(x: f64) -> Interval {
+        Interval { lo: x, hi: x }
+    }
+
+    /// Returns the lower bound of the interval.
+    pub fn lo(&self) -> f64 {
+        self.lo
+    }
+
+    /// Returns the upper bound of the interval.
+    pub fn hi(&self) -> f64 {
+        self.hi
+    }
+
+    /// Returns `true` if `x` lies within the interval.
+    pub fn contains(&self, x: f64) -> bool {
+        self.lo <= x && x <= self.hi
+    }
+
+    /// Returns the width `hi - lo` of the interval.
+    pub fn width(&self) -> f64 {
+        self.hi - self.lo
+    }
+
+    /// Returns the midpoint of the interval.
+    pub fn midpoint(&self) -> f64 {
+        self.lo / 2.0 + self.hi / 2.0
+    }
+
+    pub(crate) fn apply(self, f: MathFn) -> Result<Interval, String> {
+        match f {
+            MathFn::Abs => Ok(if self.lo >= 0.0 {
+                self
+            } else if self.hi <= 0.0 {
+                Interval::new(-self.hi, -self.lo)?
+            } else {
+                Interval::new(0.0, self.hi.max(-self.lo))?
+            }),
+            MathFn::Sqrt => {
+                if self.lo < 0.0 {
+                    return Err(
+                        "Unable to calculate the square root of an interval containing negative numbers"
+                            .to_string(),
+                    );
+                }
+                Interval::new(sqrt_down(self.lo), sqrt_up(self.hi))
+            }
+            MathFn::Exp => {
+                Interval::new(self.lo.exp().next_down().max(0.0), self.hi.exp().next_up())
+            }
+            MathFn::Ln | MathFn::Log10 => {
+                if self.lo <= 0.0 {
+                    return Err(
+                        "Unable to calculate the logarithm of an interval containing non-positive numbers"
+                            .to_string(),
+                    );
+                }
+                Interval::new(
+                    f.apply_f64(self.lo)?.next_down(),
+                    f.apply_f64(self.hi)?.next_up(),
+                )
+            }
+            MathFn::Sin => Ok(self.periodic(f64::sin, FRAC_PI_2, 3.0 * FRAC_PI_2)),
+            MathFn::Cos => Ok(self.periodic(f64::cos, 0.0, PI)),
+            MathFn::Tan => {
+                if self.width() >= PI || contains_periodic(self, FRAC_PI_2, PI) {
+                    return Err(
+                        "Unable to calculate the tangent of an interval containing a pole"
+                            .to_string(),
+                    );
+                }
+                Interval::new(self.lo.tan().next_down(), self.hi.tan().next_up())
+            }
+        }
+    }
+
+    pub(crate) fn powi(self, n: i32) -> Result<Interval, String> {
+        if n < 0 {
+            let n = n.checked_neg().ok_or("Exponent is too large")?;
+            return Interval::point(1.0) / self.powi(n)?;
+        }
+        let (a, b) = (power(self.lo, n), power(self.hi, n));
+        Ok(if n == 0 {
+            Interval::point(1.0)
+        } else if n % 2 == 1 || self.lo >= 0.0 {
+            Interval { lo: a.lo, hi: b.hi }
+        } else if self.hi <= 0.0 {
+            Interval { lo: b.lo, hi: a.hi }
+        } else {
+            Interval {
+                lo: 0.0,
+                hi: a.hi.max(b.hi),
+            }
+        })
+    }
+
+    /// Encloses the image of a `2π` periodic function with range `[-1 .. 1]`,
+    /// taking its maximum at `max_at + 2kπ` and its minimum at `min_at + 2kπ`.
+    fn periodic(self, f: fn(f64) -> f64, max_at: f64, min_at: f64) -> Interval {
+        if self.width() >= TAU {
+            return Interval { lo: -1.0, hi: 1.0 };
+        }
+        let (a, b) = (f(self.lo), f(self.hi));
+        let lo = if contains_periodic(self, min_at, TAU) {
+            -1.0
+        } else {
+            a.min(b).next_down().max(-1.0)
+        };
+        let hi = if contains_periodic(self, max_at, TAU) {
+            1.0
+        } else {
+            a.max(b).next_up().min(1.0)
+        };
+        Interval { lo, hi }
+    }
+}
+
+/// Returns `true` if some point `offset + k * period` could lie within the interval.
+///
+/// The check errs on the side of reporting a point, as `π` itself is only approximated.
+fn contains_periodic(interval: Interval, offset: f64, period: f64) -> bool {
+    let tolerance = 1e-12 * interval.lo.abs().max(interval.hi.abs()).max(1.0);
+    let k = ((interval.lo - tolerance - offset) / period).ceil();
+    offset + k * period <= interval.hi + tolerance
+}
+
+/// Encloses `x^n` by exponentiation by squaring.
+fn power(x: f64, mut n: i32) -> Interval {
+    let mut result = Interval::point(1.0);
+    let mut base = Interval::point(x);
+    while n > 0 {
+        if n % 2 == 1 {
+            result = result * base;
+        }
+        base = base * base;
+        n /= 2;
+    }
+    result
+}
+
+/// Returns `x + y` along with the exact rounding error of the sum.
+fn two_sum(x: f64, y: f64) -> (f64, f64) {
+    let sum = x + y;
+    let y_virtual = sum - x;
+    (sum, (x - (sum - y_virtual)) + (y - y_virtual))
+}
+
+/// Returns `x * y` along with the exact rounding error of the product.
+fn two_product(x: f64, y: f64) -> (f64, f64) {
+    let product = x * y;
+    // 0 * inf only occurs for bounds, where the product is 0 by convention
+    if product.is_nan() {
+        return (0.0, 0.0);
+    }
+    (product, x.mul_add(y, -product))
+}
+
+/// Returns `x / y` along with a value carrying the sign of the rounding error of the quotient.
+fn two_quotient(x: f64, y: f64) -> (f64, f64) {
+    let quotient = x / y;
+    (quotient, (-quotient).mul_add(y, x) / y)
+}
+
+fn round_down((value, error): (f64, f64)) -> f64 {
+    if error < 0.0 {
+        value.next_down()
+    } else {
+        value
+    }
+}
+
+fn round_up((value, error): (f64, f64)) -> f64 {
+    if error > 0.0 {
+        value.next_up()
+    } else {
+        value
+    }
+}
+
+fn sqrt_down(x: f64) -> f64 {
+    let root = x.sqrt();
+    round_down((root, -root.mul_add(root, -x)))
+}
+
+fn sqrt_up(x: f64) -> f64 {
+    let root = x.sqrt();
+    round_up((root, -root.mul_add(root, -x)))
+}
+
+/// Encloses all results of `op` applied to the corners of two intervals.
+fn corners(a: Interval, b: Interval, op: fn(f64, f64) -> (f64, f64)) -> Interval {
+    let results = [
+        op(a.lo, b.lo),
+        op(a.lo, b.hi),
+        op(a.hi, b.lo),
+        op(a.hi, b.hi),
+    ];
+    Interval {
+        lo: results
+            .iter()
+            .map(|&r| round_down(r))
+            .fold(f64::INFINITY, f64::min),
+        hi: results
+            .iter()
+            .map(|&r| round_up(r))
+            .fold(f64::NEG_INFINITY, f64::max),
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{} .. {}]", self.lo, self.hi)
+    }
+}
+
+impl Neg for Interval {
+    type Output = Interval;
+
+    fn neg(self) -> Interval {
+        Interval {
+            lo: -self.hi,
+            hi: -self.lo,
+        }
+    }
+}
+
+impl Add for Interval {
+    type Output = Interval;
+
+    fn add(self, other: Interval) -> Interval {
+        Interval {
+            lo: round_down(two_sum(self.lo, other.lo)),
+            hi: round_up(two_sum(self.hi, other.hi)),
+        }
+    }
+}
+
+impl Sub for Interval {
+    type Output = Interval;
+
+    fn sub(self, other: Interval) -> Interval {
+        Interval {
+            lo: round_down(two_sum(self.lo, -other.hi)),
+            hi: round_up(two_sum(self.hi, -other.lo)),
+        }
+    }
+}
+
+impl Mul for Interval {
+    type Output = Interval;
+
+    fn mul(self, other: Interval) -> Interval {
+        corners(self, other, two_product)
+    }
+}
+
+impl Div for Interval {
+    type Output = Result<Interval, String>;
+
+    fn div(self, other: Interval) -> Self::Output {
+        if other.contains(0.0) {
+            return Err("Unable to divide by an interval containing zero".to_string());
+        }
+        Ok(corners(self, other, two_quotient))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_results_stay_tight() -> Result<(), String> {
+        let a = Interval::new(1.0, 2.0)?;
+        let b = Interval::new(-3.0, 4.0)?;
+        assert_eq!(a + b, Interval::new(-2.0, 6.0)?);
+        assert_eq!(a - b, Interval::new(-3.0, 5.0)?);
+        assert_eq!(a * b, Interval::new(-6.0, 8.0)?);
+        assert_eq!((b / a)?, Interval::new(-3.0, 4.0)?);
+        Ok(())
+    }
+
+    #[test]
+    fn inexact_results_round_outward() -> Result<(), String> {
+        let sum = Interval::point(0.1) + Interval::point(0.2);
+        assert!(sum.lo() < 0.1 + 0.2 && 0.1 + 0.2 <= sum.hi());
+
+        let third = (Interval::point(1.0) / Interval::point(3.0))?;
+        assert!(third.lo() < third.hi());
+        assert!(third.contains(1.0 / 3.0));
+
+        let root = Interval::point(2.0).apply(MathFn::Sqrt)?;
+        assert!(root.lo() * root.lo() <= 2.0 && 2.0 <= root.hi() * root.hi());
+        Ok(())
+    }
+
+    #[test]
+    fn division_by_zero_interval() -> Result<(), String> {
+        let a = Interval::new(1.0, 2.0)?;
+        assert!((a / Interval::new(-1.0, 1.0)?).is_err());
+        assert!((a / Interval::new(0.0, 1.0)?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn trigonometric_extrema() -> Result<(), String> {
+        let around_max = Interval::new(1.0, 2.0)?.apply(MathFn::Sin)?;
+        assert_eq!(around_max.hi(), 1.0);
+        assert!(around_max.lo() <= 1.0_f64.sin());
+
+        let monotone = Interval::new(0.0, 1.0)?.apply(MathFn::Cos)?;
+        assert!(monotone.lo() <= 1.0_f64.cos() && monotone.hi() >= 1.0);
+
+        assert!(Interval::new(1.0, 2.0)?.apply(MathFn::Tan).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,331 @@
+use std::fmt::{self, Write};
+
+/// A JSON value, keeping numbers as written so integers and floats can be restored exactly.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Creates a number from a float, writing it with as many digits as needed to read it back exactly.
+    ///
+    /// JSON has no infinities and `NaN`, those are written as the strings `inf`, `-inf` and `NaN`.
+    pub(crate) fn float(value: f64) -> Json {
+        if value.is_finite() {
+            Json::Number(format!("{:?}", value))
+        } else {
+            Json::String(value.to_string())
+        }
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(number) => number.parse().ok(),
+            Json::String(value) => match value.as_str() {
+                "inf" => Some(f64::INFINITY),
+                "-inf" => Some(f64::NEG_INFINITY),
+                "NaN" => Some(f64::NAN),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_object(&self) -> Option<&[(String, Json)]> {
+        match self {
+            Json::Object(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Parses a JSON document.
+    pub(crate) fn parse(text: &str) -> Result<Json, String> {
+        let mut parser = Parser {
+            chars: text.char_indices().peekable(),
+        };
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+        match parser.chars.next() {
+            None => Ok(value),
+            Some((pos, c)) => Err(format!("Unexpected '{}' at position {}", c, pos)),
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            Json::Number(number) => write!(f, "{}", number),
+            Json::String(value) => write_string(f, value),
+            Json::Array(items) if items.iter().all(is_scalar) => {
+                write!(f, "[")?;
+                for (idx, item) in items.iter().enumerate() {
+                    if idx != 0 {
+                        write!(f, ", ")?;
+                    }
+                    item.write(f, indent)?;
+                }
+                write!(f, "]")
+            }
+            Json::Array(items) => {
+                writeln!(f, "[")?;
+                for (idx, item) in items.iter().enumerate() {
+                    write!(f, "{:1$}", "", indent + 2)?;
+                    item.write(f, indent + 2)?;
+                    writeln!(f, "{}", if idx + 1 < items.len() { "," } else { "" })?;
+                }
+                write!(f, "{:1$}]", "", indent)
+            }
+            Json::Object(entries) if entries.is_empty() => write!(f, "{{}}"),
+            Json::Object(entries) => {
+                writeln!(f, "{{")?;
+                for (idx, (key, value)) in entries.iter().enumerate() {
+                    write!(f, "{:1$}", "", indent + 2)?;
+                    write_string(f, key)?;
+                    write!(f, ": ")?;
+                    value.write(f, indent + 2)?;
+                    writeln!(f, "{}", if idx + 1 < entries.len() { "," } else { "" })?;
+                }
+                write!(f, "{:1$}}}", "", indent)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Json {
+    /// Writes the value as indented JSON.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+fn is_scalar(value: &Json) -> bool {
+    !matches!(value, Json::Array(_) | Json::Object(_))
+}
+
+fn write_string(f: &mut fmt::Formatter, value: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in value.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some((_, c)) if c == expected => Ok(()),
+            Some((pos, c)) => Err(format!(
+                "Expected '{}' but found '{}' at position {}",
+                expected, c, pos
+            )),
+            None => Err(format!("Expected '{}' but the document ended", expected)),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.chars.peek().copied() {
+            Some((_, '{')) => self.parse_object(),
+            Some((_, '[')) => self.parse_array(),
+            Some((_, '"')) => Ok(Json::String(self.parse_string()?)),
+            Some((_, c)) if c == '-' || c.is_ascii_digit() => {
+                let mut number = String::new();
+                while let Some((_, c)) = self.chars.next_if(|(_, c)| {
+                    c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')
+                }) {
+                    number.push(c);
+                }
+                match number.parse::<f64>() {
+                    Ok(_) => Ok(Json::Number(number)),
+                    Err(_) => Err(format!("{} is not a valid number", number)),
+                }
+            }
+            Some((pos, c)) if c.is_alphabetic() => {
+                let mut word = String::new();
+                while let Some((_, c)) = self.chars.next_if(|(_, c)| c.is_alphabetic()) {
+                    word.push(c);
+                }
+                match word.as_str() {
+                    "null" => Ok(Json::Null),
+                    "true" => Ok(Json::Bool(true)),
+                    "false" => Ok(Json::Bool(false)),
+                    _ => Err(format!("Unexpected '{}' at position {}", word, pos)),
+                }
+            }
+            Some((pos, c)) => Err(format!("Unexpected '{}' at position {}", c, pos)),
+            None => Err("Unexpected end of the document".to_string()),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut value = String::new();
+        loop {
+            match self.chars.next() {
+                Some((_, '"')) => return Ok(value),
+                Some((pos, '\\')) => match self.chars.next() {
+                    Some((_, '"')) => value.push('"'),
+                    Some((_, '\\')) => value.push('\\'),
+                    Some((_, '/')) => value.push('/'),
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, 'r')) => value.push('\r'),
+                    Some((_, 't')) => value.push('\t'),
+                    Some((_, 'b')) => value.push('\u{8}'),
+                    Some((_, 'f')) => value.push('\u{c}'),
+                    Some((_, 'u')) => {
+                        let hex: String = (0..4)
+                            .filter_map(|_| self.chars.next())
+                            .map(|(_, c)| c)
+                            .collect();
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or(format!("Invalid escape \\u{} at position {}", hex, pos))?;
+                        value.push(c);
+                    }
+                    _ => return Err(format!("Invalid escape at position {}", pos)),
+                },
+                Some((_, c)) => value.push(c),
+                None => return Err("All strings must be closed!".to_string()),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Json, String> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.chars.next_if(|(_, c)| *c == ']').is_some() {
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.parse_value()?);
+            self.skip_whitespace();
+            if self.chars.next_if(|(_, c)| *c == ',').is_none() {
+                self.expect(']')?;
+                return Ok(Json::Array(items));
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Json, String> {
+        self.expect('{')?;
+        let mut entries = Vec::new();
+        self.skip_whitespace();
+        if self.chars.next_if(|(_, c)| *c == '}').is_some() {
+            return Ok(Json::Object(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.expect(':')?;
+            entries.push((key, self.parse_value()?));
+            self.skip_whitespace();
+            if self.chars.next_if(|(_, c)| *c == ',').is_none() {
+                self.expect('}')?;
+                return Ok(Json::Object(entries));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() -> Result<(), String> {
+        let json = Json::Object(vec![
+            ("int".to_string(), Json::Number("-42".to_string())),
+            (
+                "floats".to_string(),
+                Json::Array(vec![
+                    Json::float(0.1),
+                    Json::float(1e-300),
+                    Json::float(f64::NEG_INFINITY),
+                ]),
+            ),
+            (
+                "text".to_string(),
+                Json::String("say \"hi\"\n\u{1}".to_string()),
+            ),
+            (
+                "nested".to_string(),
+                Json::Array(vec![Json::Object(Vec::new()), Json::Null, Json::Bool(true)]),
+            ),
+        ]);
+        let text = json.to_string();
+        assert_eq!(Json::parse(&text)?, json);
+        assert_eq!(
+            Json::parse(&text)?
+                .get("floats")
+                .and_then(|f| f.as_array())
+                .map(|f| f[1].as_f64()),
+            Some(Some(1e-300))
+        );
+        assert_eq!(
+            Json::parse(" [1 , \"\\u00e9\"] ")?,
+            Json::Array(vec![
+                Json::Number("1".to_string()),
+                Json::String("é".to_string())
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        assert!(Json::parse("{\"a\": 1").is_err());
+        assert!(Json::parse("[1, 2] 3").is_err());
+        assert!(Json::parse("\"open").is_err());
+        assert!(Json::parse("nope").is_err());
+        assert!(Json::parse("1.2.3").is_err());
+    }
+}
@@ -27,21 +27,48 @@
 //!
 //! # Notes
 //!
-//! This library supports the following arithmetic operations: addition (`+`), subtraction (`-`), multiplication (`*`), division (`/`), and modulo (`%`).
+//! This library supports the following arithmetic operations: addition (`+`), subtraction (`-`), multiplication (`*`), division (`/`), modulo (`%`) and exponentiation (`^`).
 //! It also supports parentheses to control the order of operations.
-//! The `eval` function can be used to evaluate equations containing variables. Variable names should be stored in a `HashMap` with their corresponding values as instances of the `Type` enum.
+//! The `eval` function can be used to evaluate equations containing variables. Variable names should be stored in a `HashMap` with their corresponding values as instances of the `Type` enum,
+//! or be looked up through the `Resolver` trait, which is also implemented for `BTreeMap` and closures.
 //!
 //! Make sure to check the documentation of the `eval` function and the `Type` enum for more details.
 //!
 //! ## Features
 //!
-//! * Arithmetic operations: `+`, `-`, `*`, `/`, `%`
+//! * Arithmetic operations: `+`, `-`, `*`, `/`, `%`, `^`, where integers that overflow are promoted to floats
 //! * Parentheses for grouping subexpressions
-//! * Variable substitution
+//! * Variable substitution from a `HashMap`, `BTreeMap`, closure or any other `Resolver`
+//! * Built-in constants `pi`, `e`, `tau`, `inf`, `nan` and physical constants like `speed_of_light` or `avogadro`
+//! * Listing the variables an equation needs, e.g. `free_variables("x * y + pi")` results in `x` and `y`
+//! * Reactive formulas in a `Workspace`, e.g. `total` defined as `price * qty` is recomputed whenever `price` or `qty` change
+//! * Spreadsheets of cells `A1`, `B2` with formulas like `=SUM(A1:A10)`, `AVG`, `MIN`, `MAX` and `COUNT`, loaded from and saved as CSV
+//! * Saving and loading sessions of variables, formulas and functions as JSON, restoring every `Type` exactly
+//! * Numbered results `$1`, `$2`, … and `_` for the last result of a session
+//! * Undoing and redoing changes to the variables of a session with a `History`
+//! * Importing variables from the environment and `.env` files, and reading them with `env("NAME", default)`
+//! * Configurable output precision and integer base, and a strict mode rejecting unknown variables
+//! * Nested scopes with shadowing, local bindings `let x = 2 in x * x` and user defined functions `let f(x) = x^2 in f(3)`
+//! * Math functions: `abs`, `sqrt`, `exp`, `ln`, `log10`, `sin`, `cos`, `tan`
+//! * Interval arithmetic with outward rounding, e.g. `[1.9 .. 2.1] * 2`
+//! * Uncertainty propagation for measurements, e.g. `9.81 ± 0.02 * 2`
+//! * Symbolic differentiation, e.g. `diff(x^2 * sin(x), x)`
+//! * Algebraic simplification, e.g. `simplify("x*1 + 0 + 2*x")` results in `3*x`
+//! * Numeric equation solving, e.g. `solve(x^2 = 2, x)`
+//! * Numeric integration, sums and products, e.g. `integrate(x^2, x, 0, 3)` or `sum(i^2, i, 1, 10)`
+//! * Minimization and maximization, e.g. `minimize((x - 2)^2, x, -10, 10)` or `maximize(f, [x, y], [0, 0])`
+//! * Ordinary differential equations, e.g. `odesolve(-2 * t * y, 1, 0, 2)`
+//! * Number theory on integers: `gcd`, `lcm`, `is_prime`, `factor`, `modpow`, `modinv`, `totient` and `digits`
+//! * Factorials `5!` and combinatorics: `nCr`, `nPr`, `binomial`, `fib` and `gamma`
+//! * Statistics over lists: `mean`, `median`, `mode`, `variance`, `stddev`, `pvariance`, `pstddev`, `percentile`, `correlation` and `linreg`
+//! * Seedable random numbers: `rand()`, `randint(a, b)`, `normal(mu, sigma)` and `choice(list)`
+//! * Financial functions like in spreadsheets: `pv`, `fv`, `pmt`, `nper`, `rate`, `npv`, `irr`, `effect`, `nominal` and `compound`
+//! * Lists, e.g. `[1, 2, 3]`
+//! * Polynomials with division, derivatives and complex roots, e.g. `roots(poly([1, -3, 2]))` or `poly(x^2 - 1, x) / poly(x - 1, x)`
 //!
 //! ## Limitations
 //!
-//! * Supports integer (`i32`), float (`f64`), and string (`String`) types only
+//! * Supports integer (`i32`), float (`f64`), string (`String`), interval (`Interval`), measurement (`Measurement`), list (`Vec<Type>`), polynomial (`Polynomial`) and function (`Function`) types only
 //! * Limited mathematical functionality
 //!
 //! # Getting Started
@@ -95,19 +122,98 @@
 //!
 //! This library is licensed under the MIT License.
 
-use std::collections::HashMap;
+use expr::Expr;
+use resolver::Resolver;
+use std::collections::{BTreeSet, HashMap};
 use type_enum::Type;
 
 /// Defines the `Type` enum for representing different types in the my-little-eval library.
 ///
-/// The `Type` enum supports three variants:
+/// The `Type` enum supports eight variants:
 /// - `Int` for integer values (`i32`)
 /// - `Float` for floating-point values (`f64`)
 /// - `String` for string values (`String`)
+/// - `Interval` for closed intervals of real numbers (`Interval`)
+/// - `Measurement` for values with a standard uncertainty (`Measurement`)
+/// - `List` for lists of values (`Vec<Type>`)
+/// - `Polynomial` for polynomials in `x` (`Polynomial`)
+/// - `Function` for user defined functions (`Function`)
 ///
 ///
 pub mod type_enum;
 
+/// Defines the `Interval` type used by `Type::Interval`.
+///
+/// Interval arithmetic rounds outward, so results are guaranteed to enclose the exact result
+/// for every combination of values within the operands.
+pub mod interval;
+
+/// Defines the `Measurement` type used by `Type::Measurement`.
+///
+/// Measurements carry a standard uncertainty, which is propagated through arithmetic
+/// and math functions using first-order error propagation, treating all operands as uncorrelated.
+pub mod measurement;
+
+/// Defines the `Polynomial` type used by `Type::Polynomial`.
+///
+/// Polynomials support arithmetic, division with remainder, derivatives and finding all complex roots.
+pub mod polynomial;
+
+/// Defines the `Expr` abstract syntax tree that equations are parsed into.
+///
+/// Expressions can be evaluated, differentiated and printed back to text.
+pub mod expr;
+
+/// Defines the built-in constants like `pi`, `e` and `speed_of_light`, which are available in every evaluation.
+///
+/// Variables of the same name take precedence, but should be treated as read-only, see `constants::is_constant`.
+pub mod constants;
+
+/// Defines the `Resolver` trait for looking up the values of variables.
+///
+/// It is implemented for `HashMap`, `BTreeMap` and closures, so values can also be looked up lazily from your own structs.
+/// A `Scope` layers bindings on top of another resolver without modifying it.
+pub mod resolver;
+
+/// Defines the `Workspace` type for spreadsheet-style variables,
+/// whose formulas are recomputed whenever the variables they depend on change.
+pub mod workspace;
+
+/// Defines the `Sheet` type, a spreadsheet of cells like `A1` holding values or formulas like `=SUM(A1:A10)`,
+/// which can be loaded from and saved as CSV.
+pub mod sheet;
+
+/// Defines the `Session` type, which saves and restores the variables, formulas and user defined functions
+/// of an interactive session to and from human-readable JSON files.
+pub mod session;
+
+/// Defines the `History` type, which records the changes inputs make to a `Session` so they can be undone and redone.
+pub mod history;
+
+/// Defines the `Config` type for settings like the precision and base results are printed in
+/// and a strict mode, in which unknown variables are errors.
+pub mod config;
+
+/// Imports variables from environment variables and `.env` files for scripting,
+/// which can also be read in equations with `env("NAME")` or `env("NAME", default)`.
+pub mod environment;
+
+mod calculus;
+mod combinatorics;
+mod finance;
+mod functions;
+mod integrate;
+mod json;
+mod number_theory;
+mod numeric;
+mod ode;
+mod optimize;
+mod parser;
+mod random;
+mod simplify;
+mod solve;
+mod statistics;
+
 /// Initializes an empty `HashMap` to store variables and their corresponding values as instances of ´Type´.
 ///
 /// # Returns
@@ -142,7 +248,6 @@ pub mod type_enum;
 /// The `HashMap` should have variable names as keys (`String`) and their corresponding values (`Type`).
 /// The `Type` enum can represent different types such as integers, floats, or strings.
 /// The resulting `HashMap` can be used as input to the `eval` function for evaluating equations containing variables.
-
 pub fn vars_init() -> HashMap<String, Type> {
     HashMap::new()
 }
@@ -151,7 +256,7 @@ pub fn vars_init() -> HashMap<String, Type> {
 /// # Arguments
 ///
 /// * `equation` - A string containing the mathematical equation to evaluate.
-/// * `vars` - An optional reference to a `HashMap` of variable names and their corresponding values (`Type`).
+/// * `vars` - An optional reference to a `Resolver`, e.g. a `HashMap` of variable names and their corresponding values (`Type`).
 ///
 /// # Returns
 ///
@@ -180,143 +285,366 @@ pub fn vars_init() -> HashMap<String, Type> {
 ///
 /// # Notes
 ///
-/// This function evaluates equations containing basic arithmetic operators: `+`, `-`, `*`, `/`, and `%`.
+/// This function evaluates equations containing basic arithmetic operators: `+`, `-`, `*`, `/`, `%` and `^`.
 /// It supports parentheses to control the order of operations.
+/// The equation is parsed into an `Expr` first, see `Expr::parse` to evaluate the same equation repeatedly.
 /// The `vars` argument allows for the evaluation of equations with variables.
-/// If variables are provided, they should be stored in a `HashMap` with variable names as keys and their corresponding values as instances of `Type`.
-
-pub fn eval(equation: &str, vars: Option<&HashMap<String, Type>>) -> Result<Type, String> {
-    let mut operand_builder = String::new();
-    let mut operand_right: Option<Type> = None;
-    let mut operator: char = '\0';
-    let mut operand_left: Result<Type, String> = Err("Error: Empty equation".to_string());
-    let mut open_parentheses_count = 0;
-    let mut idx_of_parentheses: usize = 0;
-    let mut is_first_operand = true;
-    let mut operator_expected = false;
-    for (idx, c) in equation.chars().enumerate() {
-        if open_parentheses_count != 0 {
-            match c {
-                '(' => open_parentheses_count += 1,
-                ')' => {
-                    open_parentheses_count -= 1;
-                    if open_parentheses_count == 0 {
-                        if idx - idx_of_parentheses == 1 {
-                            continue;
-                        }
-                        operator_expected = true;
-                        operand_right = Some(eval(&equation[idx_of_parentheses + 1..idx], vars)?);
-
-                        continue;
-                    }
-                }
-                _ => continue,
-            }
-            continue;
-        }
-
-        match c {
-            '+' | '-' | '*' | '/' | '%' => {
-                if !operator_expected {
-                    if c == '-' {
-                        operand_builder.push(c);
-                        continue;
-                    }
-                    return Err(format!("{} can't be followed by {}", operator, c));
-                }
-
-                if !operand_builder.is_empty() {
-                    operand_right = Some(var_or_string(&operand_builder, vars));
-                    operand_builder.clear();
-                }
-
-                //correct order of operation e.g. * before +
-                let order_of_op = c != '+' && c != '-' && (operator == '+' || operator == '-');
-                if order_of_op {
-                    return handle_op(
-                        operand_left,
-                        eval(
-                            &format!(
-                                "{}{}{}",
-                                operand_right.unwrap(),
-                                c,
-                                equation[idx + 1..].to_owned()
-                            ),
-                            vars,
-                        )?,
-                        operator,
-                        is_first_operand,
-                    );
-                }
-                operator_expected = false;
-
-                if let Some(r) = operand_right.clone() {
-                    operand_left = handle_op(operand_left, r, operator, is_first_operand);
-                    is_first_operand = false;
-                }
-                operator = c;
-                continue;
-            }
-            '(' => {
-                open_parentheses_count += 1;
-                idx_of_parentheses = idx;
-            }
-            ')' => {
-                return Err(String::from(
-                    "parentheses must be opened before being closed",
-                ))
-            }
-            ' ' => (),
-            _ => {
-                operand_builder.push(c);
-                operator_expected = true;
-            }
-        }
-    }
-    if !operand_builder.is_empty() {
-        if operand_builder == "-" {
-            operand_builder = String::from("-1");
-            operator = '*';
-        }
-        operand_right = Some(var_or_string(&operand_builder, vars));
-    }
-    if let Some(r) = operand_right {
-        operand_left = handle_op(operand_left, r, operator, is_first_operand);
-    }
+/// If variables are provided, they can be stored in a `HashMap` or `BTreeMap` with variable names as keys and their corresponding values as instances of `Type`,
+/// or looked up lazily by a closure or any other implementation of the `Resolver` trait.
+/// Functions are called by name with comma separated arguments, e.g. `sqrt(2)`, and intervals are written as `[lower .. upper]`.
+/// Measurements are written as `value ± uncertainty`, where `±` binds tighter than any other operator.
+pub fn eval(equation: &str, vars: Option<&dyn Resolver>) -> Result<Type, String> {
+    Expr::parse(equation)?.eval(vars)
+}
 
-    if open_parentheses_count != 0 {
-        return Err("All parentheses must be closed!".to_string());
-    }
-    operand_left
+/// Lists the variables an equation needs to be evaluated, e.g. to prompt for missing inputs.
+///
+/// # Arguments
+///
+/// * `equation` - A string containing the mathematical equation to inspect.
+///
+/// # Returns
+///
+/// A `Result` containing the sorted names of the variables,
+/// or a `String` with an error message if the equation can't be parsed.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::free_variables;
+///
+/// let names = free_variables("price * qty * (1 + tax(rate))").unwrap();
+/// assert_eq!(names.into_iter().collect::<Vec<String>>(), ["price", "qty", "rate"]);
+/// ```
+///
+/// # Notes
+///
+/// Function names are not variables, neither are the built-in constants like `pi`
+/// and the variables bound within the equation, e.g. by `let x = 2 in x` or the `i` of `sum(i^2, i, 1, n)`.
+/// See `Expr::free_variables` to inspect an equation that was already parsed.
+pub fn free_variables(equation: &str) -> Result<BTreeSet<String>, String> {
+    Ok(Expr::parse(equation)?.free_variables())
 }
 
-fn handle_op(
-    operand_left: Result<Type, String>,
-    operand_right: Type,
-    operator: char,
-    is_first_operand: bool,
-) -> Result<Type, String> {
-    if is_first_operand {
-        return Ok(operand_right);
-    }
-    match operator {
-        '+' => operand_left? + operand_right,
-        '-' => operand_left? - operand_right,
-        '*' => operand_left? * operand_right,
-        '/' => operand_left? / operand_right,
-        '%' => operand_left? % operand_right,
-        _ => Err(format!(
-            "Unexpected Error: {} is not an valid operator",
-            operator
-        )),
-    }
+/// Differentiates an equation with respect to the variable `var`.
+///
+/// # Arguments
+///
+/// * `equation` - A string containing the mathematical equation to differentiate.
+/// * `var` - The name of the variable to differentiate by.
+///
+/// # Returns
+///
+/// A `Result` containing the derivative as an `Expr`, which can be printed or evaluated,
+/// or a `String` with an error message if the equation can't be parsed or differentiated.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::{diff, vars_init, type_enum::Type};
+///
+/// let derivative = diff("x^2 + sin(x)", "x").unwrap();
+/// assert_eq!(derivative.to_string(), "2*x + cos(x)");
+///
+/// let mut variables = vars_init();
+/// variables.insert("x".to_string(), Type::Int(0));
+/// assert_eq!(derivative.eval(Some(&variables)), Ok(Type::Float(1.0)));
+/// ```
+///
+/// # Notes
+///
+/// Within equations, derivatives can be taken with `diff(expr, x)`, which results in the derivative as a function of `x`,
+/// e.g. `let d = diff(x^2, x) in d(3)` results in `6`.
+pub fn diff(equation: &str, var: &str) -> Result<Expr, String> {
+    Expr::parse(equation)?.derivative(var)
 }
 
-fn var_or_string(operand: &str, vars: Option<&HashMap<String, Type>>) -> Type {
-    match vars {
-        Some(vars) => vars.get(operand).unwrap_or(&Type::from(operand)).clone(),
-        None => Type::from(operand),
-    }
+/// Simplifies an equation into a canonical form.
+///
+/// # Arguments
+///
+/// * `equation` - A string containing the mathematical equation to simplify.
+///
+/// # Returns
+///
+/// A `Result` containing the simplified `Expr`, which can be printed back to text or evaluated
+/// with the same result as the original equation, or a `String` with an error message if the equation can't be parsed.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::simplify;
+///
+/// assert_eq!(simplify("x*1 + 0 + 2*x").unwrap().to_string(), "3*x");
+/// assert_eq!(simplify("(a + (b + 1)) - (a - 1)").unwrap().to_string(), "b + 2");
+/// ```
+///
+/// # Notes
+///
+/// Simplification flattens nested sums and products, combines like terms, cancels terms like `x - x` and folds constants.
+pub fn simplify(equation: &str) -> Result<Expr, String> {
+    Ok(Expr::parse(equation)?.simplify())
+}
+
+/// Numerically finds all roots of an equation in one variable within a range.
+///
+/// # Arguments
+///
+/// * `equation` - A string containing the equation to solve, either `a = b` or an expression that should equal zero.
+/// * `var` - The name of the variable to solve for.
+/// * `lo`, `hi` - The range to search for roots.
+/// * `vars` - An optional reference to a `Resolver`, e.g. a `HashMap`, for the other variables of the equation.
+///
+/// # Returns
+///
+/// A `Result` containing the sorted roots found in the range,
+/// or a `String` with an error message if no roots were found or the root finding did not converge.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::{solve, vars_init, type_enum::Type};
+///
+/// let mut variables = vars_init();
+/// variables.insert("a".to_string(), Type::Int(2));
+///
+/// let roots = solve("x^2 = a", "x", -10.0, 10.0, Some(&variables)).unwrap();
+/// assert_eq!(roots, vec![-2f64.sqrt(), 2f64.sqrt()]);
+///
+/// assert!(solve("x^2 = -1", "x", -10.0, 10.0, None).is_err());
+/// ```
+///
+/// # Notes
+///
+/// The variable is bound as a `Type::Float` on top of `vars` every time the equation is evaluated.
+/// Within equations, `solve(a = b, x)` searches `[-100 .. 100]` and `solve(a = b, x, lo, hi)` the given range,
+/// both resulting in a `Type::List` of the roots.
+pub fn solve(
+    equation: &str,
+    var: &str,
+    lo: f64,
+    hi: f64,
+    vars: Option<&dyn Resolver>,
+) -> Result<Vec<f64>, String> {
+    solve::solve(&Expr::parse(equation)?, var, lo, hi, vars)
+}
+
+/// Numerically integrates an equation over the variable `var` from `a` to `b`.
+///
+/// # Arguments
+///
+/// * `equation` - A string containing the mathematical equation to integrate.
+/// * `var` - The name of the variable to integrate over.
+/// * `a`, `b` - The bounds of the integral, which may be infinite.
+/// * `vars` - An optional reference to a `Resolver`, e.g. a `HashMap`, for the other variables of the equation.
+///
+/// # Returns
+///
+/// A `Result` containing the value of the integral,
+/// or a `String` with an error message if the equation can't be evaluated or the integral did not converge.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::integrate;
+///
+/// let area = integrate("x^2", "x", 0.0, 3.0, None).unwrap();
+/// assert!((area - 9.0).abs() < 1e-12);
+///
+/// let tail = integrate("exp(-x)", "x", 0.0, f64::INFINITY, None).unwrap();
+/// assert!((tail - 1.0).abs() < 1e-12);
+/// ```
+///
+/// # Notes
+///
+/// The integral is computed with adaptive Gauss–Kronrod quadrature, parsing the equation only once.
+/// Within equations, integrals are written as `integrate(expr, x, a, b)`,
+/// and sums and products over an integer index as `sum(expr, i, from, to)` and `prod(expr, i, from, to)`.
+pub fn integrate(
+    equation: &str,
+    var: &str,
+    a: f64,
+    b: f64,
+    vars: Option<&dyn Resolver>,
+) -> Result<f64, String> {
+    integrate::integrate(&Expr::parse(equation)?, var, a, b, vars)
+}
+
+/// Finds the minimum of an equation over the variable `var` in the range `[lo .. hi]`.
+///
+/// # Arguments
+///
+/// * `equation` - A string containing the mathematical equation to minimize.
+/// * `var` - The name of the variable to minimize over.
+/// * `lo`, `hi` - The range to search for the minimum.
+/// * `vars` - An optional reference to a `Resolver`, e.g. a `HashMap`, for the other variables of the equation.
+///
+/// # Returns
+///
+/// A `Result` containing the argmin and the minimal value,
+/// or a `String` with an error message if the equation can't be evaluated or the search did not converge.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::{minimize, maximize};
+///
+/// let (x, value) = minimize("(x - 2)^2 + 1", "x", -10.0, 10.0, None).unwrap();
+/// assert!((x - 2.0).abs() < 1e-6 && (value - 1.0).abs() < 1e-12);
+///
+/// let (x, value) = maximize("x * (10 - x)", "x", 0.0, 10.0, None).unwrap();
+/// assert!((x - 5.0).abs() < 1e-6 && (value - 25.0).abs() < 1e-12);
+/// ```
+///
+/// # Notes
+///
+/// The minimum is found with Brent's method, which combines golden-section search with parabolic interpolation.
+/// If the equation has several local minima in the range, one of them is returned, unless a bound is even lower.
+/// Within equations, `minimize(expr, x, lo, hi)` results in the `Type::List` `[argmin, value]`,
+/// while `minimize(expr, [x, y], [x0, y0])` searches several variables from a starting point, see `minimize_multi`.
+pub fn minimize(
+    equation: &str,
+    var: &str,
+    lo: f64,
+    hi: f64,
+    vars: Option<&dyn Resolver>,
+) -> Result<(f64, f64), String> {
+    optimize::optimize(&Expr::parse(equation)?, var, lo, hi, false, vars)
+}
+
+/// Finds the maximum of an equation over the variable `var` in the range `[lo .. hi]`.
+///
+/// Works just like `minimize`, returning the argmax and the maximal value.
+pub fn maximize(
+    equation: &str,
+    var: &str,
+    lo: f64,
+    hi: f64,
+    vars: Option<&dyn Resolver>,
+) -> Result<(f64, f64), String> {
+    optimize::optimize(&Expr::parse(equation)?, var, lo, hi, true, vars)
+}
+
+/// Finds a local minimum of an equation over several variables, starting the search at `start`.
+///
+/// # Arguments
+///
+/// * `equation` - A string containing the mathematical equation to minimize.
+/// * `names` - The names of the variables to minimize over.
+/// * `start` - The starting value of each variable.
+/// * `vars` - An optional reference to a `Resolver`, e.g. a `HashMap`, for the other variables of the equation.
+///
+/// # Returns
+///
+/// A `Result` containing the argmin and the minimal value,
+/// or a `String` with an error message if the equation can't be evaluated or the search did not converge.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::minimize_multi;
+///
+/// let (point, value) = minimize_multi("(x - 1)^2 + (y + 2)^2", &["x", "y"], &[0.0, 0.0], None).unwrap();
+/// assert!((point[0] - 1.0).abs() < 1e-6 && (point[1] + 2.0).abs() < 1e-6);
+/// assert!(value < 1e-12);
+/// ```
+///
+/// # Notes
+///
+/// The minimum is found with the Nelder–Mead method, which doesn't need derivatives.
+/// Within equations, this is written as `minimize(expr, [x, y], [x0, y0])` or `maximize(expr, [x, y], [x0, y0])`.
+pub fn minimize_multi(
+    equation: &str,
+    names: &[&str],
+    start: &[f64],
+    vars: Option<&dyn Resolver>,
+) -> Result<(Vec<f64>, f64), String> {
+    optimize::optimize_multi(&Expr::parse(equation)?, names, start, false, vars)
+}
+
+/// Finds a local maximum of an equation over several variables, starting the search at `start`.
+///
+/// Works just like `minimize_multi`, returning the argmax and the maximal value.
+pub fn maximize_multi(
+    equation: &str,
+    names: &[&str],
+    start: &[f64],
+    vars: Option<&dyn Resolver>,
+) -> Result<(Vec<f64>, f64), String> {
+    optimize::optimize_multi(&Expr::parse(equation)?, names, start, true, vars)
+}
+
+/// Numerically solves the ordinary differential equation `dy/dt = rhs` with the initial value `y(t0) = y0`.
+///
+/// # Arguments
+///
+/// * `rhs` - A string containing the right-hand side of the differential equation in terms of `t` and `y`.
+/// * `y0` - The initial value of `y` at `t0`.
+/// * `t0`, `t1` - The start and the end of the solution, `t1` may be smaller than `t0`.
+/// * `steps` - The number of fixed steps, or `None` to adapt the step size to the local error.
+/// * `vars` - An optional reference to a `Resolver`, e.g. a `HashMap`, for the other variables of the equation.
+///
+/// # Returns
+///
+/// A `Result` containing the `(t, y)` samples from `t0` to `t1`,
+/// or a `String` with an error message if the right-hand side can't be evaluated or the solution blows up.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::{odesolve, vars_init, type_enum::Type};
+///
+/// let mut variables = vars_init();
+/// variables.insert("k".to_string(), Type::Float(-0.5));
+///
+/// let samples = odesolve("k * y", 2.0, 0.0, 4.0, Some(40), Some(&variables)).unwrap();
+/// assert_eq!(samples.len(), 41);
+///
+/// let (t, y) = *odesolve("k * y", 2.0, 0.0, 4.0, None, Some(&variables)).unwrap().last().unwrap();
+/// assert_eq!(t, 4.0);
+/// assert!((y - 2.0 * (-2f64).exp()).abs() < 1e-9);
+/// ```
+///
+/// # Notes
+///
+/// Fixed steps use the classical fourth order Runge–Kutta method, adaptive steps the Dormand–Prince 5(4) method.
+/// Within equations, `odesolve(rhs, y0, t0, t1)` and `odesolve(rhs, y0, t0, t1, steps)`
+/// result in a `Type::List` of `[t, y]` lists.
+pub fn odesolve(
+    rhs: &str,
+    y0: f64,
+    t0: f64,
+    t1: f64,
+    steps: Option<i32>,
+    vars: Option<&dyn Resolver>,
+) -> Result<Vec<(f64, f64)>, String> {
+    ode::odesolve(&Expr::parse(rhs)?, y0, t0, t1, steps, vars)
+}
+
+/// Seeds the random number generator used by `rand`, `randint`, `normal` and `choice`.
+///
+/// # Arguments
+///
+/// * `seed` - The seed, equal seeds result in equal sequences of random numbers.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::{eval, seed};
+///
+/// seed(42);
+/// let first = eval("randint(1, 6) + rand()", None).unwrap();
+///
+/// seed(42);
+/// assert_eq!(eval("randint(1, 6) + rand()", None).unwrap(), first);
+/// ```
+///
+/// # Notes
+///
+/// Every thread has its own generator, which is randomly seeded until `seed` is called,
+/// so Monte Carlo formulas are reproducible by seeding before evaluating them.
+pub fn seed(seed: u64) {
+    random::seed(seed)
 }
 
 #[cfg(test)]
@@ -421,4 +749,229 @@ mod tests {
         )?;
         Ok(())
     }
+    #[test]
+    fn functions() -> Result<(), String> {
+        eval_assert("sqrt(16) + 1", "5.0")?;
+        eval_assert("2 * abs(3 - 5)", "4")?;
+        eval_assert("-abs(-2) * 3", "-6")?;
+        eval_assert("abs(-2147483647 - 1)", "2147483648.0")?;
+        eval_assert("lcm(4, 6) + gcd(12, 18)", "18")?;
+        eval_assert("modpow(3, totient(10), 10)", "1")?;
+        eval_assert("nCr(5, 2) + nPr(5, 2) + fib(10)", "85")?;
+        eval_assert("13! / 13", "479001600.0")?;
+        eval_assert("2147483647 + 1", "2147483648.0")?;
+        eval_assert("mean([1, 2, 3, 4]) + median([5, 1, 3])", "5.5")?;
+        eval_assert("fv(0, 12, -100) + npv(0, [1, 2])", "1203.0")?;
+        assert_eq!(
+            eval("irr([1, 2])", None),
+            Err("irr needs at least one positive and one negative cash flow".to_string())
+        );
+        assert!(eval("gcd(1.5, 3)", None).is_err());
+        Ok(())
+    }
+    #[test]
+    fn intervals() -> Result<(), String> {
+        eval_assert("[1 .. 2] * 2 + 1", "[3 .. 5]")?;
+        eval_assert("[-1 .. 2] * [3 .. 4]", "[-4 .. 8]")?;
+        eval_assert("abs([-3 .. 2])", "[0 .. 3]")?;
+        assert!(eval("1 / [-1 .. 1]", None).is_err());
+        assert!(eval("[2 .. 1]", None).is_err());
+        Ok(())
+    }
+    #[test]
+    fn measurements() -> Result<(), String> {
+        eval_assert("9.81 ± 0.02 * 2", "19.62 ± 0.04")?;
+        eval_assert("3 ± 0.3 + 4 ± 0.4", "7 ± 0.5")?;
+
+        let mut vars = vars_init();
+        vars.insert("g".to_string(), Type::Float(9.81));
+        vars.insert("dg".to_string(), Type::Float(0.02));
+        assert_eq!(eval("g ± dg", Some(&vars)), Ok(Type::from("9.81 ± 0.02")));
+        assert!(eval("1 ± -1", None).is_err());
+        Ok(())
+    }
+    #[test]
+    fn operator_precedence() -> Result<(), String> {
+        eval_assert("1 - 2 * 3 - 4", "-9")?;
+        eval_assert("2 ^ 3 ^ 2", "512")?;
+        eval_assert("-2 ^ 2 + 10 % 4", "-2")?;
+        eval_assert("2 ^ -1", "0.5")?;
+        eval_assert("-3! ^ 2", "-36")?;
+        eval_assert("2 * 3!!", "1440")?;
+        Ok(())
+    }
+    #[test]
+    fn derivatives() -> Result<(), String> {
+        assert_eq!(
+            eval("diff(x ^ 2 - 3 * x, x)", None)?.to_string(),
+            "(x) -> 2*x - 3"
+        );
+        eval_assert("let d = diff(x ^ 2, x) in d(3) + 1", "7")?;
+        eval_assert("let d = diff(a * x ^ 3, x) in let a = 2 in d(1)", "6")?;
+        assert!(eval("diff(x ^ 2, x) + 1", None).is_err());
+        assert!(eval("diff(x ^ 2, 2)", None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn series_and_integrals() -> Result<(), String> {
+        eval_assert("sum(i ^ 2, i, 1, 10)", "385")?;
+        eval_assert("prod(i, i, 1, 5) * 2", "240")?;
+        eval_assert("sum(i * x, i, 1, 3)", "xxxxxx")?;
+        assert!(eval("sum(i, i, 1, 2.5)", None).is_err());
+        assert!((eval("integrate(x, x, 0, 2)", None)?.as_f64().unwrap() - 2.0).abs() < 1e-12);
+        Ok(())
+    }
+
+    #[test]
+    fn optimization() -> Result<(), String> {
+        let Type::List(result) = eval("minimize(x ^ 2 - 4 * x, x, -10, 10)", None)? else {
+            panic!("minimize should result in a list");
+        };
+        assert!((result[0].as_f64().unwrap() - 2.0).abs() < 1e-6);
+        assert!((result[1].as_f64().unwrap() + 4.0).abs() < 1e-12);
+
+        let Type::List(result) = eval("maximize(-(x - 1) ^ 2 - y ^ 2, [x, y], [0, 1])", None)?
+        else {
+            panic!("maximize should result in a list");
+        };
+        assert!(matches!(&result[0], Type::List(point) if point.len() == 2));
+        assert!(eval("minimize(x, x, 1)", None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn differential_equations() -> Result<(), String> {
+        assert_eq!(
+            eval("odesolve(2 * t, 0, 0, 1, 2)", None)?,
+            Type::List(vec![
+                Type::List(vec![Type::Float(0.0), Type::Float(0.0)]),
+                Type::List(vec![Type::Float(0.5), Type::Float(0.25)]),
+                Type::List(vec![Type::Float(1.0), Type::Float(1.0)]),
+            ])
+        );
+        assert!(eval("odesolve(y, 1, 0, 1, 2.5)", None).is_err());
+        assert!(eval("odesolve(y, 1, 0)", None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn polynomials() -> Result<(), String> {
+        assert_eq!(eval("poly([1, -3, 2])", None)?.to_string(), "x^2 - 3x + 2");
+        assert_eq!(
+            eval("poly((t - 1) * (t - 2), t)", None)?,
+            eval("poly([1, -3, 2])", None)?
+        );
+        assert_eq!(
+            eval("poly(x^3 + 1, x) % poly(x^2 + 1, x)", None)?.to_string(),
+            "-x + 1"
+        );
+        assert_eq!(
+            eval("polyval(poly([1, -3, 2]), 4)", None)?,
+            Type::Float(6.0)
+        );
+        assert_eq!(
+            eval("polyder(poly([1, -3, 2]))", None)?.to_string(),
+            "2x - 3"
+        );
+        assert_eq!(
+            eval("roots(poly(x^2 - 3*x + 2, x))", None)?.to_string(),
+            "[[1, 0], [2, 0]]"
+        );
+        assert!(eval("poly(sqrt(x), x)", None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn constants() -> Result<(), String> {
+        assert_eq!(eval("sin(pi / 2)", None)?, Type::Float(1.0));
+        assert_eq!(eval("tau / pi", None)?, Type::Float(2.0));
+        assert_eq!(eval("ln(e)", None)?, Type::Float(1.0));
+        assert_eq!(eval("1 / inf", None)?, Type::Float(0.0));
+        assert_eq!(eval("speed_of_light", None)?, Type::Float(299_792_458.0));
+
+        let mut variables = vars_init();
+        variables.insert("e".to_string(), Type::Int(2));
+        assert_eq!(eval("e^2", Some(&variables))?, Type::Int(4));
+        assert_eq!(eval("let pi = 3 in pi", None)?, Type::Int(3));
+        Ok(())
+    }
+
+    #[test]
+    fn free_variables_of_equations() -> Result<(), String> {
+        let names = |equation: &str| -> Result<Vec<String>, String> {
+            Ok(free_variables(equation)?.into_iter().collect())
+        };
+        assert_eq!(names("2 * x + y - x")?, ["x", "y"]);
+        assert_eq!(names("sin(pi * t) + e")?, ["t"]);
+        assert_eq!(names("let a = b in a * c")?, ["b", "c"]);
+        assert_eq!(names("let f(x) = x * k in f(z)")?, ["k", "z"]);
+        assert_eq!(names("solve(x^2 = a, x, lo, hi)")?, ["a", "hi", "lo"]);
+        assert_eq!(names("integrate(x * w, x, 0, b)")?, ["b", "w"]);
+        assert_eq!(
+            names("minimize(x^2 + y^2 + c, [x, y], [s, 0])")?,
+            ["c", "s"]
+        );
+        assert_eq!(names("odesolve(-k * t * y, y0, 0, 2)")?, ["k", "y0"]);
+        assert_eq!(names("diff(x^2, x)")?, Vec::<String>::new());
+        assert!(free_variables("(x").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn scopes() -> Result<(), String> {
+        eval_assert("let x = 2 in x * x", "4")?;
+        eval_assert("let x = 2 in let x = x + 1 in x * x", "9")?;
+        eval_assert("2 * (let x = 3 in x) + 1", "7")?;
+        eval_assert("let f(x, y) = x^2 + y in f(3, 1)", "10")?;
+        eval_assert("let a = 10 in let f(x) = x + a in f(1)", "11")?;
+        eval_assert("let f() = 5 in f()", "5")?;
+        // functions keep the variables and functions of where they are defined
+        eval_assert("let a = 10 in let f(x) = x + a in let a = 1 in f(1)", "11")?;
+        eval_assert(
+            "let g(x) = x * 2 in let f(x) = g(x) in let g(x) = 0 in f(3)",
+            "6",
+        )?;
+        eval_assert("let a = 10 in let f(a) = a in let a = 1 in f(5)", "5")?;
+        assert_eq!(
+            eval("let a = 10 in let f(x) = x + a in f", None)?.to_string(),
+            "(x) -> let a = 10 in x + a"
+        );
+
+        let mut variables = vars_init();
+        variables.insert("x".to_string(), Type::Int(100));
+        assert_eq!(
+            eval("let f(x) = x + 1 in f(1) + x", Some(&variables))?,
+            Type::Int(102)
+        );
+        assert_eq!(
+            eval("(let x = 1 in x) + x", Some(&variables))?,
+            Type::Int(101)
+        );
+        assert_eq!(variables.len(), 1);
+
+        assert_eq!(
+            eval("let f(x) = x in f(1, 2)", None),
+            Err("f: Expected 1 argument(s) but got 2".to_string())
+        );
+        // a function calling itself is looked up when it is called
+        assert!(eval("let f(x) = f(x) in f(1)", None)
+            .is_err_and(|err| err.ends_with("Unable to nest more than 100 function calls")));
+        Ok(())
+    }
+
+    #[test]
+    fn equations() -> Result<(), String> {
+        assert_eq!(
+            eval("solve(2 * x + 1 = 7, x)", None)?,
+            Type::List(vec![Type::Float(3.0)])
+        );
+        assert_eq!(
+            eval("solve(x ^ 2 - 4, x, 0, 10)", None)?,
+            Type::List(vec![Type::Float(2.0)])
+        );
+        assert!(eval("1 = 1", None).is_err());
+        assert!(eval("solve(x ^ 2 = -1, x)", None).is_err());
+        Ok(())
+    }
 }
@@ -1,86 +1,446 @@
+use std::env;
+use std::fs;
 use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process;
+
+use my_little_eval::config::Config;
+use my_little_eval::constants::{self, CONSTANTS};
+use my_little_eval::environment;
+use my_little_eval::expr::{Expr, Function};
+use my_little_eval::history::{Change, History};
+use my_little_eval::session::Session;
+use my_little_eval::sheet::Sheet;
+use my_little_eval::type_enum::Type;
+
+/// The default directory of the init script and config, `$XDG_CONFIG_HOME/my-little-eval` or `~/.config/my-little-eval`.
+fn config_dir() -> Option<PathBuf> {
+    let dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(dir.join("my-little-eval"))
+}
+
+/// The command line options of the shell.
+struct Options {
+    init: Option<PathBuf>,
+    config: Option<PathBuf>,
+    env_prefix: Option<String>,
+    dotenv: Option<PathBuf>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options {
+            init: None,
+            config: None,
+            env_prefix: None,
+            dotenv: None,
+        };
+        while let Some(arg) = args.next() {
+            let Some(value) = args.next() else {
+                return Err(format!("{} is missing a value\n\n{}", arg, usage()));
+            };
+            match arg.as_str() {
+                "--init" => options.init = Some(PathBuf::from(value)),
+                "--config" => options.config = Some(PathBuf::from(value)),
+                "--env-prefix" => options.env_prefix = Some(value),
+                "--dotenv" => options.dotenv = Some(PathBuf::from(value)),
+                _ => return Err(format!("Unknown option {}\n\n{}", arg, usage())),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn usage() -> &'static str {
+    "usage: my-little-eval [--init <file>] [--config <file>] [--env-prefix <prefix>] [--dotenv <file>]
+    --init <file>          run the let lines and function definitions of <file> on startup
+                           (default: ~/.config/my-little-eval/init.calc)
+    --config <file>        read the settings precision, strict and base from <file>
+                           (default: ~/.config/my-little-eval/config)
+    --env-prefix <prefix>  import the environment variables starting with <prefix> as variables
+                           without the prefix, e.g. CALC_rate as rate with --env-prefix CALC_
+    --dotenv <file>        import the NAME=value lines of <file> as variables and environment variables"
+}
+
+/// Reads the file given by an option, or the file `name` in the config directory if it exists.
+fn read_startup_file(path: Option<PathBuf>, name: &str) -> Option<(PathBuf, String)> {
+    let (path, required) = match path {
+        Some(path) => (path, true),
+        None => (config_dir()?.join(name), false),
+    };
+    match fs::read_to_string(&path) {
+        Ok(text) => Some((path, text)),
+        Err(err) if required || err.kind() != io::ErrorKind::NotFound => {
+            eprintln!("Unable to read {}: {}", path.display(), err);
+            None
+        }
+        Err(_) => None,
+    }
+}
 
 fn main() {
-    println!("run help for instructions");
-    let mut vars = my_little_eval::vars_init();
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(2);
+        }
+    };
+    let mut session = Session::new();
+    let mut config = Config::default();
+    if let Some((path, text)) = read_startup_file(options.config, "config") {
+        match Config::parse(&text) {
+            Ok(parsed) => config = parsed,
+            Err(error) => eprintln!("{}: {}", path.display(), error),
+        }
+    }
+    if let Some(path) = options.dotenv {
+        match environment::load_dotenv(path) {
+            Ok(entries) => {
+                for (name, value) in entries {
+                    // so env() reads the entries too, without overriding the environment
+                    if env::var_os(&name).is_none() {
+                        env::set_var(&name, &value);
+                    }
+                    import_var(&mut session, &name, Type::from(value.as_str()));
+                }
+            }
+            Err(error) => eprintln!("{}", error),
+        }
+    }
+    if let Some(prefix) = options.env_prefix {
+        for (name, value) in environment::vars_from_env(&prefix) {
+            import_var(&mut session, &name, value);
+        }
+    }
+    if let Some((path, text)) = read_startup_file(options.init, "init.calc") {
+        for error in run_init(&text, &mut session, &mut config) {
+            eprintln!("{}:{}", path.display(), error);
+        }
+    }
 
+    println!("run help for instructions");
+    let mut history = History::new();
     loop {
         let mut input = String::new();
-        let mut args = Vec::new();
 
         print!(">>>");
         io::stdout().flush().unwrap();
         match io::stdin().read_line(&mut input) {
+            Ok(0) => break,
             Ok(_) => (),
             Err(_) => continue,
         }
-        if !input.is_ascii() {
-            eprintln!("UTF-8 is unsupported for now");
+        // commands of the history are not recorded in it themselves
+        match input.trim() {
+            "undo" => match history.undo(&mut session) {
+                Some(change) => {
+                    println!("undid {}", change.input());
+                    print_variables(current_values(change, &session), &config);
+                }
+                None => eprintln!("There is nothing to undo"),
+            },
+            "redo" => match history.redo(&mut session) {
+                Some(change) => {
+                    println!("redid {}", change.input());
+                    print_variables(current_values(change, &session), &config);
+                }
+                None => eprintln!("There is nothing to redo"),
+            },
+            "history" => {
+                for (idx, change) in history.changes().enumerate() {
+                    println!("{}: {}", idx + 1, change.input());
+                    print_variables(change.after(), &config);
+                }
+            }
+            line => {
+                let before = session.clone();
+                let result = run(line, &mut session, &mut config);
+                history.record(line, before, &session);
+                match result {
+                    Ok(true) => (),
+                    Ok(false) => break,
+                    Err(error) => eprintln!("{}", error),
+                }
+            }
         }
-        input = input
-            .chars()
-            .filter(|c| !c.is_whitespace() || c == &' ')
-            .collect();
-        input.split(' ').for_each(|f| args.push(f));
-        if args.is_empty() {
+    }
+}
+
+/// Returns the variables changed by `change` with their current values in `session`.
+fn current_values<'a>(
+    change: &'a Change,
+    session: &'a Session,
+) -> impl Iterator<Item = (&'a str, Option<&'a Type>)> {
+    change
+        .changed()
+        .iter()
+        .map(|name| (name.as_str(), session.workspace().values().get(name)))
+}
+
+/// Prints the values of variables, indented below an input.
+fn print_variables<'a>(
+    variables: impl Iterator<Item = (&'a str, Option<&'a Type>)>,
+    config: &Config,
+) {
+    for (name, value) in variables {
+        match value {
+            Some(value) => println!(
+                "    {}: {} => {}",
+                name,
+                value.get_type(),
+                config.format(value)
+            ),
+            None => println!("    {}: missing", name),
+        }
+    }
+}
+
+/// Sets an imported variable unless it is read-only or not a valid name.
+fn import_var(session: &mut Session, name: &str, value: Type) {
+    let imported = is_valid_var(name, |name| session.is_read_only(name))
+        .and_then(|_| session.workspace_mut().set(name, value));
+    if let Err(error) = imported {
+        eprintln!("Unable to import {}: {}", name, error);
+    }
+}
+
+/// Runs the definitions of an init script, returning the errors prefixed with their line numbers.
+///
+/// Only variables, formulas and functions are defined with `let`, `let!` and `const`, and settings changed with `set`,
+/// all other lines are errors, so the script can't print results or touch any files.
+fn run_init(text: &str, session: &mut Session, config: &mut Config) -> Vec<String> {
+    let mut errors = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
             continue;
         }
-        match args[0] {
-            "let" => {
-                let s = args[1..].join("");
-                if !s.contains('=') {
-                    eprintln!("command is missing '='");
-                    continue;
-                }
-                let parts: Vec<&str> = s.split('=').collect();
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let result = match command {
+            "let" if is_assignment(rest) => run(line, session, config),
+            "let!" | "const" | "set" => run(line, session, config),
+            _ => Err(
+                "only let, let! and const definitions and set are allowed in the init script"
+                    .to_string(),
+            ),
+        };
+        if let Err(error) = result {
+            errors.push(format!("{}: {}", idx + 1, error));
+        }
+    }
+    errors
+}
 
-                if parts.len() != 2 {
-                    eprintln!("wrong usage of the let keyword");
-                    continue;
-                }
-                let var_name = parts[0];
-                let equation = parts[1];
-                if let Err(err) = is_valid_var(var_name) {
-                    eprintln!("{}", err);
-                    continue;
+/// Runs a line of input, returning `false` once the user quits.
+fn run(input: &str, session: &mut Session, config: &mut Config) -> Result<bool, String> {
+    if !input.chars().all(|c| c.is_ascii() || c == '±') {
+        eprintln!("UTF-8 is unsupported for now");
+    }
+    let input: String = input
+        .chars()
+        .filter(|c| !c.is_whitespace() || c == &' ')
+        .collect();
+    let args: Vec<&str> = input.split(' ').collect();
+    match args[0] {
+        "let" if !is_assignment(&args[1..].join(" ")) => {
+            let res = config.eval(&args.join(" "), Some(session.workspace()))?;
+            store_result(session, res, config);
+        }
+        command @ ("let" | "let!" | "const") => {
+            let s = args[1..].join(" ");
+            let Some((var_name, equation)) = s.split_once('=') else {
+                return Err("command is missing '='".to_string());
+            };
+            // the equation keeps its spaces, which separate e.g. `let` and `in` of a bound value
+            let var_name = var_name.replace(' ', "");
+            let var_name = var_name.as_str();
+            if var_name.is_empty() {
+                return Err(format!("wrong usage of the {} keyword", command));
+            }
+            let protected = |name: &str| command != "let!" && session.is_read_only(name);
+            if let Some(name) = var_name.strip_suffix(':') {
+                is_valid_var(name, protected)?;
+                let workspace = session.workspace();
+                config.check(&Expr::parse(equation)?, |var| {
+                    workspace.values().contains_key(var) || workspace.formula(var).is_some()
+                })?;
+                let defined = session.workspace_mut().define(name, equation);
+                if command == "const" && session.workspace().formula(name).is_some() {
+                    session.set_read_only(name);
                 }
-
-                match my_little_eval::eval(equation, Some(&vars)) {
-                    Ok(res) => {
-                        vars.insert(var_name.to_string(), res);
+                return defined.map(|_| true);
+            }
+            let (name, value) = match var_name.split_once('(') {
+                Some((name, params)) => (
+                    name,
+                    parse_function(params, equation, |name| session.is_read_only(name))
+                        .map(Type::Function),
+                ),
+                None => (var_name, config.eval(equation, Some(session.workspace()))),
+            };
+            is_valid_var(name, protected)?;
+            let set = session.workspace_mut().set(name, value?);
+            if command == "const" {
+                session.set_read_only(name);
+            }
+            set?;
+        }
+        "derive" => {
+            let s = join_args(&args[1..]);
+            let Some((equation, var)) = s.rsplit_once(',').filter(|(_, var)| !var.is_empty())
+            else {
+                return Err("command is missing ', <variable>'".to_string());
+            };
+            println!("d/d{} = {}", var, my_little_eval::diff(equation, var)?);
+        }
+        "solve" => {
+            let s = join_args(&args[1..]);
+            let res = config.eval(&format!("solve({})", s), Some(session.workspace()))?;
+            store_result(session, res, config);
+        }
+        "free" => {
+            let names = my_little_eval::free_variables(&args[1..].join(" "))?;
+            println!("Variables:");
+            for name in names {
+                match session.workspace().values().get(&name) {
+                    Some(value) => {
+                        println!("{}: {} => {}", name, value.get_type(), config.format(value))
                     }
-                    Err(error) => {
-                        eprintln!("{}", error);
+                    None => println!("{}: missing", name),
+                }
+            }
+        }
+        "sheet" => {
+            let sheet = Sheet::load(args[1..].join(" "))?;
+            for (cell, input) in sheet.cells() {
+                match sheet.value(&cell) {
+                    Some(value) if input.starts_with('=') => println!(
+                        "{}: {} => {} {}",
+                        cell,
+                        value.get_type(),
+                        config.format(value),
+                        input
+                    ),
+                    Some(value) => {
+                        println!("{}: {} => {}", cell, value.get_type(), config.format(value))
                     }
+                    None => println!("{}: error {}", cell, input),
                 }
             }
-            "q" | "quit" => {
-                break;
+        }
+        "save" => session.save(args[1..].join(" "))?,
+        "load" => *session = Session::load(args[1..].join(" "))?,
+        "set" => match args[1..] {
+            [key, value] => config.set(key, value)?,
+            _ => return Err("command must be set <setting> <value>".to_string()),
+        },
+        "seed" => match args.get(1).and_then(|seed| seed.parse::<u64>().ok()) {
+            Some(seed) => my_little_eval::seed(seed),
+            None => return Err("command is missing a non-negative integer seed".to_string()),
+        },
+        "q" | "quit" => return Ok(false),
+        "constants" => {
+            println!("Constants:");
+            for (name, value, description) in CONSTANTS {
+                if value.is_finite() && !(1e-3..1e9).contains(&value.abs()) {
+                    println!("{}: {:e} ({})", name, value, description)
+                } else {
+                    println!("{}: {} ({})", name, value, description)
+                }
             }
-            "vars" => {
-                println!("Variables:");
-                for (k, v) in &vars {
-                    println!("{}: {} => {}", k, v.get_type(), v,)
+        }
+        "vars" => {
+            println!("Variables:");
+            let vars = session.workspace();
+            // the numbered results are listed by the command results
+            let numbered = |name: &str| {
+                name.strip_prefix('$')
+                    .is_some_and(|n| n.parse::<usize>().is_ok())
+            };
+            for (k, v) in vars.values().iter().filter(|(k, _)| !numbered(k)) {
+                match vars.formula(k) {
+                    Some(formula) => {
+                        println!(
+                            "{}: {} => {} := {}",
+                            k,
+                            v.get_type(),
+                            config.format(v),
+                            formula
+                        )
+                    }
+                    None => println!("{}: {} => {}", k, v.get_type(), config.format(v)),
                 }
             }
-            "help" => println!("{}", help_string()),
+        }
+        "results" => {
+            println!("Results:");
+            for (number, value) in session.results() {
+                println!(
+                    "${}: {} => {}",
+                    number,
+                    value.get_type(),
+                    config.format(value)
+                );
+            }
+        }
+        "help" => println!("{}", help_string()),
 
-            _ => match my_little_eval::eval(&args.join(""), Some(&vars)) {
-                Ok(res) => {
-                    println!("res: {} = {}", res.get_type(), res);
-                    vars.insert("res".to_string(), res);
-                }
-                Err(error) => {
-                    eprintln!("{}", error);
-                }
-            },
-        };
+        _ => {
+            let res = config.eval(&join_args(&args), Some(session.workspace()))?;
+            store_result(session, res, config);
+        }
+    }
+    Ok(true)
+}
+
+/// Prints the result of an evaluation and stores it as the next numbered result, `_` and `res`.
+fn store_result(session: &mut Session, res: Type, config: &Config) {
+    println!(
+        "res (${}): {} = {}",
+        session.next_result_number(),
+        res.get_type(),
+        config.format(&res)
+    );
+    if let Err(error) = session.push_result(res) {
+        eprintln!("{}", error);
     }
 }
 
-fn is_valid_var(s: &str) -> Result<(), String> {
-    if s.chars().next().unwrap().is_numeric() {
-        return Err("Variables cannot start with number".to_string());
+/// Joins the space separated arguments of a command, keeping the spaces within quoted strings.
+fn join_args(args: &[&str]) -> String {
+    let mut joined = String::new();
+    let (mut quoted, mut escaped) = (false, false);
+    for c in args.join(" ").chars() {
+        match c {
+            ' ' if !quoted => continue,
+            '"' if !escaped => quoted = !quoted,
+            _ => (),
+        }
+        escaped = quoted && c == '\\' && !escaped;
+        joined.push(c);
+    }
+    joined
+}
+
+/// Returns `true` if the arguments of a `let` command assign a variable, formula or function,
+/// e.g. `x = 2` or `f(x) = let a = 2 in a * x`, instead of being the rest of a `let ... in` expression
+/// like `x = 2 in x * x`, whose `in` is not part of the assigned equation.
+fn is_assignment(s: &str) -> bool {
+    s.split_once('=')
+        .is_none_or(|(_, equation)| Expr::parse(equation).is_ok())
+}
+
+fn is_valid_var(s: &str, is_read_only: impl Fn(&str) -> bool) -> Result<(), String> {
+    match s.chars().next() {
+        None => return Err("Variables must have a name".to_string()),
+        Some(c) if c.is_numeric() => {
+            return Err("Variables cannot start with number".to_string());
+        }
+        Some(_) => (),
     }
     for c in s.chars() {
         if !c.is_alphanumeric() && c != '_' {
@@ -90,15 +450,154 @@ fn is_valid_var(s: &str) -> Result<(), String> {
             ));
         }
     }
+    if is_read_only(s) {
+        let kind = match constants::is_constant(s) {
+            true => "a built-in constant",
+            false => "read-only",
+        };
+        return Err(format!("{} is {}, use let! to redefine it anyway", s, kind));
+    }
     Ok(())
 }
 
+/// Parses the definition of a function from its comma separated parameters,
+/// followed by the closing parenthesis, and its body.
+fn parse_function(
+    params: &str,
+    body: &str,
+    is_read_only: impl Fn(&str) -> bool,
+) -> Result<Function, String> {
+    let Some(params) = params.strip_suffix(')') else {
+        return Err("parameters must be closed with ')'".to_string());
+    };
+    let params = params
+        .split(',')
+        .filter(|param| !param.is_empty())
+        .map(|param| is_valid_var(param, &is_read_only).map(|_| param.to_string()))
+        .collect::<Result<Vec<String>, String>>()?;
+    Ok(Function::new(params, Expr::parse(body)?))
+}
+
 fn help_string() -> String {
     "help page:
     Define a variable with the let keyword eg. let hallo = 2
+    Define a formula that is recomputed whenever its variables change with := eg. let total := price * qty
+    Calculate a spreadsheet from a CSV file with cells like A1 and formulas like =SUM( A1:A10 ) eg. sheet prices.csv
+    Define a read-only variable with the const keyword eg. const g0 = 9.81 or overwrite a read-only variable with let! eg. let! g0 = 9.8
+    Use the built-in constants eg. sin( pi / 2 ) or avogadro * 2 and list them with command constants
+    Define a function with the let keyword eg. let f( x, y ) = x ^ 2 + y and call it eg. f( 3, 1 )
+    Bind variables locally with let and in eg. let x = 2 in x * x or let f( x ) = x ^ 2 in f( 3 )
     Evaluate a term, space separated eg. ( 1 + hallo ) * 2
+    Call a function eg. sqrt( 2 ) or use an interval eg. [ 1.9 .. 2.1 ] * 2
+    Add an uncertainty to a value eg. 9.81 ± 0.02
+    Differentiate a term with the derive keyword eg. derive x ^ 2 * sin( x ), x
+    Solve an equation numerically with the solve keyword eg. solve x ^ 2 = 2, x or solve cos( x ) = x, x, 0, 1
+    Optimize a term eg. minimize( ( x - 2 ) ^ 2, x, -10, 10 ) or maximize( -x ^ 2 - y ^ 2, [ x, y ], [ 1, 1 ] )
+    Solve dy/dt = f(t, y) eg. odesolve( -2 * t * y, 1, 0, 2 ) or with a fixed number of steps odesolve( y, 1, 0, 1, 10 )
+    Use number theory on integers eg. gcd( 12, 18 ), factor( 360 ), modpow( 4, 13, 497 ) or digits( 1203 )
+    Calculate factorials eg. 5! or 0.5! and combinatorics eg. nCr( 5, 2 ), nPr( 5, 2 ), binomial( 5, 2 ), fib( 10 ) or gamma( 0.5 )
+    Calculate statistics of lists eg. mean( [ 1, 2, 3 ] ), stddev( xs ), percentile( xs, 90 ) or linreg( xs, ys )
+    Draw random numbers eg. rand( ), randint( 1, 6 ), normal( 0, 1 ) or choice( [ 1, 2, 3 ] ) and seed them eg. seed 42
+    Use financial functions eg. pmt( 0.005, 360, 200000 ), npv( 0.1, [ -100, 60, 60 ] ) or irr( [ -100, 60, 60 ] )
+    Create a polynomial eg. poly( [ 1, -3, 2 ] ) or poly( x ^ 2 - 1, x ) and use polyval( p, 2 ), polyder( p ) or roots( p )
+    Integrate a term eg. integrate( x ^ 2, x, 0, 3 ) or add up terms eg. sum( i ^ 2, i, 1, 10 ) or prod( i, i, 1, 5 )
     Quit the program with command q || quit
     Print out all variables with command vars
+    Change a setting eg. set precision 4, set base 16 or set strict true, see the config file ~/.config/my-little-eval/config
+    Read an environment variable eg. env( \"HOME\" ) or with a default eg. env( \"RATE\", 0.05 ), see the options --env-prefix and --dotenv
+    Run let lines and function definitions on startup from ~/.config/my-little-eval/init.calc or the file given with --init
+    Use earlier results by their number eg. $1 * 2 or the last result with _ eg. _ + 1 and print them out with command results
+    Undo the last change of variables with command undo and redo it with command redo
+    Print out the inputs that changed variables with their new values with command history
+    Save the variables, formulas and functions eg. save session.json and restore them eg. load session.json
+    Print out the variables a term needs with the free keyword eg. free x * y + pi
     Print this help page"
         .to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_spaces_in_strings() {
+        assert_eq!(
+            join_args(&["env(", "\"a", "b\",", "\"c\\\"", "d\"", ")"]),
+            "env(\"a b\",\"c\\\" d\")"
+        );
+    }
+
+    #[test]
+    fn rejects_empty_names() {
+        let mut session = Session::new();
+        let mut config = Config::default();
+        for input in ["let (x) = 3", "let := 3", "let ( ) = 1"] {
+            assert_eq!(
+                run(input, &mut session, &mut config),
+                Err("Variables must have a name".to_string())
+            );
+        }
+        assert!(session.workspace().values().is_empty());
+    }
+
+    #[test]
+    fn let_assignments_and_expressions() -> Result<(), String> {
+        let mut session = Session::new();
+        let mut config = Config::default();
+        let value = |session: &Session, name: &str| session.workspace().values().get(name).cloned();
+        run("let t = in", &mut session, &mut config)?;
+        assert_eq!(value(&session, "t"), Some(Type::from("in")));
+        run("let s = \"a in b\"", &mut session, &mut config)?;
+        assert_eq!(
+            value(&session, "s"),
+            Some(Type::String("a in b".to_string()))
+        );
+        run("let k = let g(x) = x * 3 in g", &mut session, &mut config)?;
+        run("let y = k(2) + 1", &mut session, &mut config)?;
+        assert_eq!(value(&session, "y"), Some(Type::Int(7)));
+        run("let f(x) = let a = 2 in a * x", &mut session, &mut config)?;
+        assert!(matches!(value(&session, "f"), Some(Type::Function(_))));
+
+        run("let x = 2 in x * x", &mut session, &mut config)?;
+        assert_eq!(value(&session, "res"), Some(Type::Int(4)));
+        assert_eq!(value(&session, "x"), None);
+        run("let h(x) = x ^ 2 in h( 3 )", &mut session, &mut config)?;
+        assert_eq!(value(&session, "res"), Some(Type::Int(9)));
+        assert_eq!(value(&session, "h"), None);
+        assert!(run("let x = 2 +", &mut session, &mut config).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn init_script() {
+        let mut session = Session::new();
+        let mut config = Config::default();
+        let script = "# constants\nconst g0 = 9.81\nlet f( x ) = x * g0\n\nlet w := f( 2 )\nset precision 2\n\
+            1 + 1\nlet x = 2 in x\nsave session.json\nquit\nlet! g0 = 10";
+        let errors = run_init(script, &mut session, &mut config);
+        assert_eq!(errors.len(), 4);
+        assert!(errors[0].starts_with("7: only let"));
+        assert!(errors[3].starts_with("10: "));
+        assert_eq!(
+            session.workspace().values().get("w"),
+            Some(&Type::Float(19.62))
+        );
+        assert_eq!(session.workspace().values().get("g0"), Some(&Type::Int(10)));
+        assert_eq!(session.next_result_number(), 1);
+        assert_eq!(config.precision, Some(2));
+    }
+
+    #[test]
+    fn strict_formulas() -> Result<(), String> {
+        let mut session = Session::new();
+        let mut config = Config::parse("strict = true")?;
+        assert_eq!(
+            run("let y := x + 1", &mut session, &mut config),
+            Err("Unknown variable x".to_string())
+        );
+        run("let x := 2 * pi", &mut session, &mut config)?;
+        run("let y := x + 1", &mut session, &mut config)?;
+        assert!(session.workspace().formula("y").is_some());
+        Ok(())
+    }
+}
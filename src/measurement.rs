@@ -0,0 +1,241 @@
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+use crate::functions::MathFn;
+
+/// Represents a measured value `value ± uncertainty` with a standard uncertainty.
+///
+/// Uncertainties are propagated through arithmetic and math functions using first-order
+/// error propagation, treating all operands as uncorrelated.
+/// This also holds for a measurement combined with itself, so `g + g` has a smaller uncertainty than `2 * g`
+/// and `g - g` is not exact. Scale a measurement by an exact number to avoid this.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::measurement::Measurement;
+///
+/// let g = Measurement::new(9.81, 0.02).unwrap();
+/// let doubled = Measurement::exact(2.0) * g;
+/// assert_eq!(doubled.value(), 19.62);
+/// assert_eq!(doubled.to_string(), "19.62 ± 0.04");
+///
+/// let sum = Measurement::new(3.0, 0.3).unwrap() + Measurement::new(4.0, 0.4).unwrap();
+/// assert_eq!(sum.to_string(), "7.0 ± 0.5");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Measurement {
+    value: f64,
+    uncertainty: f64,
+}
+
+impl Measurement {
+    /// Creates the measurement `value ± uncertainty`.
+    ///
+    /// Returns an error if the uncertainty is negative or if one of the arguments is `NaN`.
+    pub fn new(value: f64, uncertainty: f64) -> Result<Measurement, String> {
+        if value.is_nan() || uncertainty.is_nan() {
+            return Err("Measurements must consist of numbers".to_string());
+        }
+        if uncertainty < 0.0 {
+            return Err(format!(
+                "The uncertainty of a measurement must not be negative, got {}",
+                uncertainty
+            ));
+        }
+        Ok(Measurement { value, uncertainty })
+    }
+
+    /// Creates a measurement of `value` without any uncertainty.
+    pub fn exact(value: f64) -> Measurement {
+        Measurement {
+            value,
+            uncertainty: 0.0,
+        }
+    }
+
+    /// Returns the measured value.
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// Returns the standard uncertainty of the measured value.
+    pub fn uncertainty(&self) -> f64 {
+        self.uncertainty
+    }
+
+    pub(crate) fn apply(self, f: MathFn) -> Result<Measurement, String> {
+        Measurement::new(
+            f.apply_f64(self.value)?,
+            (f.derivative_f64(self.value) * self.uncertainty).abs(),
+        )
+    }
+
+    pub(crate) fn pow(self, other: Measurement) -> Result<Measurement, String> {
+        let value = self.value.powf(other.value);
+        if value.is_nan() {
+            return Err("Unable to raise a negative number to a fractional power".to_string());
+        }
+        Ok(self.propagate(
+            other,
+            value,
+            other.value * self.value.powf(other.value - 1.0),
+            value * self.value.ln(),
+        ))
+    }
+
+    /// Combines two measurements into `value` with the given partial derivatives of the operation.
+    fn propagate(self, other: Measurement, value: f64, d_self: f64, d_other: f64) -> Measurement {
+        // exact operands don't contribute, even where the derivative is undefined
+        let contribution = |derivative: f64, uncertainty: f64| {
+            if uncertainty == 0.0 {
+                0.0
+            } else {
+                derivative * uncertainty
+            }
+        };
+        Measurement {
+            value,
+            uncertainty: contribution(d_self, self.uncertainty)
+                .hypot(contribution(d_other, other.uncertainty)),
+        }
+    }
+}
+
+impl fmt::Display for Measurement {
+    /// Prints the uncertainty with one significant figure, or two if its leading digit is a 1,
+    /// and rounds the value to the same decimal place.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.uncertainty == 0.0 || !self.uncertainty.is_finite() || !self.value.is_finite() {
+            return write!(f, "{} ± {}", self.value, self.uncertainty);
+        }
+        let exponent = self.uncertainty.log10().floor() as i32;
+        let leading_digit = (self.uncertainty / 10f64.powi(exponent)).floor();
+        let significant_figures = if leading_digit < 2.0 { 2 } else { 1 };
+        let decimals = significant_figures - 1 - exponent;
+        if decimals >= 0 {
+            let decimals = decimals as usize;
+            write!(
+                f,
+                "{:.*} ± {:.*}",
+                decimals, self.value, decimals, self.uncertainty
+            )
+        } else {
+            let scale = 10f64.powi(-decimals);
+            write!(
+                f,
+                "{} ± {}",
+                (self.value / scale).round() * scale,
+                (self.uncertainty / scale).round() * scale
+            )
+        }
+    }
+}
+
+impl Neg for Measurement {
+    type Output = Measurement;
+
+    fn neg(self) -> Measurement {
+        Measurement {
+            value: -self.value,
+            uncertainty: self.uncertainty,
+        }
+    }
+}
+
+impl Add for Measurement {
+    type Output = Measurement;
+
+    fn add(self, other: Measurement) -> Measurement {
+        self.propagate(other, self.value + other.value, 1.0, 1.0)
+    }
+}
+
+impl Sub for Measurement {
+    type Output = Measurement;
+
+    fn sub(self, other: Measurement) -> Measurement {
+        self.propagate(other, self.value - other.value, 1.0, -1.0)
+    }
+}
+
+impl Mul for Measurement {
+    type Output = Measurement;
+
+    fn mul(self, other: Measurement) -> Measurement {
+        self.propagate(other, self.value * other.value, other.value, self.value)
+    }
+}
+
+impl Div for Measurement {
+    type Output = Result<Measurement, String>;
+
+    fn div(self, other: Measurement) -> Self::Output {
+        if other.value == 0.0 {
+            return Err("Unable to divide by zero".to_string());
+        }
+        Ok(self.propagate(
+            other,
+            self.value / other.value,
+            1.0 / other.value,
+            -self.value / (other.value * other.value),
+        ))
+    }
+}
+
+impl Rem for Measurement {
+    type Output = Result<Measurement, String>;
+
+    fn rem(self, other: Measurement) -> Self::Output {
+        if other.value == 0.0 {
+            return Err("Unable to calculate the remainder with a divisor of zero".to_string());
+        }
+        Ok(self.propagate(
+            other,
+            self.value % other.value,
+            1.0,
+            -(self.value / other.value).trunc(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn propagation() -> Result<(), String> {
+        let a = Measurement::new(3.0, 0.3)?;
+        let b = Measurement::new(4.0, 0.4)?;
+        assert_eq!(a + b, Measurement::new(7.0, 0.5)?);
+        assert_eq!(a - b, Measurement::new(-1.0, 0.5)?);
+
+        let product = a * b;
+        assert_eq!(product.value(), 12.0);
+        assert!((product.uncertainty() - 12.0 * 0.1f64.hypot(0.1)).abs() < 1e-12);
+
+        let root = Measurement::new(16.0, 0.8)?.apply(MathFn::Sqrt)?;
+        assert_eq!(root, Measurement::new(4.0, 0.1)?);
+        Ok(())
+    }
+
+    #[test]
+    fn exact_operands_keep_uncertainty() -> Result<(), String> {
+        let g = Measurement::new(9.81, 0.02)?;
+        assert_eq!(g * Measurement::exact(2.0), Measurement::new(19.62, 0.04)?);
+        assert!((g / Measurement::exact(0.0)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn significant_figures() -> Result<(), String> {
+        assert_eq!(Measurement::new(9.81, 0.02)?.to_string(), "9.81 ± 0.02");
+        assert_eq!(
+            Measurement::new(9.8123, 0.0149)?.to_string(),
+            "9.812 ± 0.015"
+        );
+        assert_eq!(Measurement::new(1234.5, 56.0)?.to_string(), "1230 ± 60");
+        assert_eq!(Measurement::new(2.5, 0.0)?.to_string(), "2.5 ± 0");
+        Ok(())
+    }
+}
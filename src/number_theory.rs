@@ -0,0 +1,225 @@
+use std::ops::RangeInclusive;
+
+use crate::functions;
+use crate::type_enum::Type;
+
+/// Calls the number theory function `name` on integer arguments, returning `None` if there is no such function.
+pub(crate) fn call(name: &str, args: &[Type]) -> Option<Result<Type, String>> {
+    let arity = match name {
+        "is_prime" | "factor" | "totient" => 1..=1,
+        "gcd" | "lcm" | "modinv" => 2..=2,
+        "modpow" => 3..=3,
+        "digits" => 1..=2,
+        _ => return None,
+    };
+    Some(
+        int_args(name, args, arity).and_then(|args| match (name, args.as_slice()) {
+            ("gcd", &[a, b]) => Ok(int(gcd(a, b))),
+            ("lcm", &[a, b]) => Ok(int(lcm(a, b))),
+            ("is_prime", &[n]) => Ok(Type::Int(is_prime(n) as i32)),
+            ("factor", &[n]) => Ok(Type::List(factor(n)?.into_iter().map(int).collect())),
+            ("modpow", &[b, e, m]) => Ok(int(modpow(b, e, m)?)),
+            ("modinv", &[a, m]) => Ok(int(modinv(a, m)?)),
+            ("totient", &[n]) => Ok(int(totient(n)?)),
+            ("digits", &[n]) => digits(n, 10),
+            ("digits", &[n, base]) => digits(n, base),
+            _ => Err(format!("Unknown function {}", name)),
+        }),
+    )
+}
+
+/// Checks the number of arguments and converts them into `i64`s, so intermediate results can't overflow.
+fn int_args(name: &str, args: &[Type], arity: RangeInclusive<usize>) -> Result<Vec<i64>, String> {
+    functions::check_arity(name, args, arity)?;
+    args.iter()
+        .map(|arg| match arg {
+            Type::Int(value) => Ok(*value as i64),
+            arg => Err(format!(
+                "{} expects integers but got {}",
+                name,
+                arg.get_type()
+            )),
+        })
+        .collect()
+}
+
+/// Results that don't fit into an `i32` are promoted to floats.
+fn int(value: i64) -> Type {
+    i32::try_from(value).map_or(Type::Float(value as f64), Type::Int)
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+fn lcm(a: i64, b: i64) -> i64 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    // the arguments are at most 2^31 in magnitude, so this fits into an i64
+    (a / gcd(a, b) * b).abs()
+}
+
+fn is_prime(n: i64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    (2..).take_while(|d| d * d <= n).all(|d| n % d != 0)
+}
+
+fn factor(mut n: i64) -> Result<Vec<i64>, String> {
+    if n <= 0 {
+        return Err("Unable to factor a non-positive number".to_string());
+    }
+    let mut factors = Vec::new();
+    let mut d = 2;
+    while d * d <= n {
+        while n % d == 0 {
+            factors.push(d);
+            n /= d;
+        }
+        d += 1;
+    }
+    if n > 1 {
+        factors.push(n);
+    }
+    Ok(factors)
+}
+
+fn modpow(base: i64, exponent: i64, modulus: i64) -> Result<i64, String> {
+    if modulus <= 0 {
+        return Err("Unable to calculate a power modulo a non-positive number".to_string());
+    }
+    let mut base = if exponent < 0 {
+        modinv(base, modulus)?
+    } else {
+        base.rem_euclid(modulus)
+    };
+    let mut exponent = exponent.unsigned_abs();
+    let mut result = 1 % modulus;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exponent >>= 1;
+    }
+    Ok(result)
+}
+
+fn modinv(a: i64, modulus: i64) -> Result<i64, String> {
+    if modulus <= 0 {
+        return Err(
+            "Unable to calculate the modular inverse with a non-positive modulus".to_string(),
+        );
+    }
+    // extended Euclidean algorithm, keeping track of the coefficient of a
+    let (mut r0, mut r1) = (a.rem_euclid(modulus), modulus);
+    let (mut s0, mut s1) = (1, 0);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (s0, s1) = (s1, s0 - q * s1);
+    }
+    if r0 != 1 {
+        return Err(format!("{} has no inverse modulo {}", a, modulus));
+    }
+    Ok(s0.rem_euclid(modulus))
+}
+
+fn totient(n: i64) -> Result<i64, String> {
+    if n <= 0 {
+        return Err("Unable to calculate the totient of a non-positive number".to_string());
+    }
+    let mut primes = factor(n)?;
+    primes.dedup();
+    Ok(primes.into_iter().fold(n, |result, p| result / p * (p - 1)))
+}
+
+fn digits(n: i64, base: i64) -> Result<Type, String> {
+    if n < 0 {
+        return Err("Unable to calculate the digits of a negative number".to_string());
+    }
+    if base < 2 {
+        return Err(format!("Unable to calculate the digits in base {}", base));
+    }
+    let mut digits = vec![Type::Int((n % base) as i32)];
+    let mut n = n / base;
+    while n > 0 {
+        digits.push(Type::Int((n % base) as i32));
+        n /= base;
+    }
+    digits.reverse();
+    Ok(Type::List(digits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call_ints(name: &str, args: &[i32]) -> Result<Type, String> {
+        functions::call(name, args.iter().map(|arg| Type::Int(*arg)).collect())
+    }
+
+    #[test]
+    fn divisibility() -> Result<(), String> {
+        assert_eq!(call_ints("gcd", &[12, -18])?, Type::Int(6));
+        assert_eq!(call_ints("gcd", &[0, 0])?, Type::Int(0));
+        assert_eq!(call_ints("lcm", &[4, 6])?, Type::Int(12));
+        assert_eq!(
+            call_ints("lcm", &[i32::MAX, i32::MAX - 1])?,
+            Type::Float(i32::MAX as f64 * (i32::MAX - 1) as f64)
+        );
+        assert_eq!(call_ints("gcd", &[i32::MIN, 0])?, Type::Float(2147483648.0));
+        Ok(())
+    }
+
+    #[test]
+    fn primes() -> Result<(), String> {
+        assert_eq!(call_ints("is_prime", &[2_147_483_647])?, Type::Int(1));
+        assert_eq!(call_ints("is_prime", &[1])?, Type::Int(0));
+        assert_eq!(
+            call_ints("factor", &[360])?.to_string(),
+            "[2, 2, 2, 3, 3, 5]"
+        );
+        assert_eq!(call_ints("factor", &[1])?, Type::List(Vec::new()));
+        assert_eq!(call_ints("totient", &[36])?, Type::Int(12));
+        assert!(call_ints("factor", &[0]).is_err());
+        assert!(call_ints("totient", &[-3]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn modular_arithmetic() -> Result<(), String> {
+        assert_eq!(call_ints("modpow", &[4, 13, 497])?, Type::Int(445));
+        assert_eq!(call_ints("modpow", &[3, -1, 7])?, Type::Int(5));
+        assert_eq!(call_ints("modinv", &[-3, 7])?, Type::Int(2));
+        assert_eq!(
+            call_ints("modpow", &[2, 0, 0]),
+            Err("Unable to calculate a power modulo a non-positive number".to_string())
+        );
+        assert_eq!(
+            call_ints("modinv", &[2, 4]),
+            Err("2 has no inverse modulo 4".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn digits_and_arguments() -> Result<(), String> {
+        assert_eq!(call_ints("digits", &[1203])?.to_string(), "[1, 2, 0, 3]");
+        assert_eq!(call_ints("digits", &[10, 2])?.to_string(), "[1, 0, 1, 0]");
+        assert_eq!(call_ints("digits", &[0])?.to_string(), "[0]");
+        assert!(call_ints("digits", &[-1]).is_err());
+        assert_eq!(
+            call_ints("gcd", &[1]),
+            Err("gcd expects 2 argument(s) but got 1".to_string())
+        );
+        assert!(call("gcd", &[Type::Float(1.5), Type::Int(2)]).is_some_and(|res| res.is_err()));
+        Ok(())
+    }
+}
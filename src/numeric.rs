@@ -0,0 +1,68 @@
+use crate::expr::Expr;
+use crate::resolver::{Resolver, Scope};
+use crate::type_enum::Type;
+
+/// Evaluates an expression as a real function of one of its variables,
+/// by repeatedly binding the variable on top of the other variables.
+pub(crate) struct RealFunction<'a> {
+    expr: &'a Expr,
+    var: &'a str,
+    vars: Scope<'a>,
+}
+
+impl<'a> RealFunction<'a> {
+    pub(crate) fn new(
+        expr: &'a Expr,
+        var: &'a str,
+        vars: Option<&'a dyn Resolver>,
+    ) -> RealFunction<'a> {
+        RealFunction {
+            expr,
+            var,
+            vars: Scope::new(vars),
+        }
+    }
+
+    pub(crate) fn eval(&mut self, x: f64) -> Result<f64, String> {
+        self.vars.set(self.var, Type::Float(x));
+        let value = self.expr.eval(Some(&self.vars))?;
+        value.as_f64().ok_or(format!(
+            "Expected {} to be a number but got {}",
+            self.expr,
+            value.get_type()
+        ))
+    }
+}
+
+/// Evaluates an expression as a real function of several of its variables.
+pub(crate) struct MultiFunction<'a> {
+    expr: &'a Expr,
+    names: &'a [&'a str],
+    vars: Scope<'a>,
+}
+
+impl<'a> MultiFunction<'a> {
+    pub(crate) fn new(
+        expr: &'a Expr,
+        names: &'a [&'a str],
+        vars: Option<&'a dyn Resolver>,
+    ) -> MultiFunction<'a> {
+        MultiFunction {
+            expr,
+            names,
+            vars: Scope::new(vars),
+        }
+    }
+
+    pub(crate) fn eval(&mut self, point: &[f64]) -> Result<f64, String> {
+        for (name, x) in self.names.iter().zip(point) {
+            self.vars.set(name, Type::Float(*x));
+        }
+        let value = self.expr.eval(Some(&self.vars))?;
+        value.as_f64().ok_or(format!(
+            "Expected {} to be a number but got {}",
+            self.expr,
+            value.get_type()
+        ))
+    }
+}
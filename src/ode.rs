@@ -0,0 +1,215 @@
+use crate::expr::Expr;
+use crate::numeric::MultiFunction;
+use crate::resolver::Resolver;
+
+/// The Dormand–Prince tableau, row `i` holds the coefficients for stage `i + 1`.
+const A: [[f64; 6]; 6] = [
+    [1.0 / 5.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0],
+    [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0],
+    [
+        19372.0 / 6561.0,
+        -25360.0 / 2187.0,
+        64448.0 / 6561.0,
+        -212.0 / 729.0,
+        0.0,
+        0.0,
+    ],
+    [
+        9017.0 / 3168.0,
+        -355.0 / 33.0,
+        46732.0 / 5247.0,
+        49.0 / 176.0,
+        -5103.0 / 18656.0,
+        0.0,
+    ],
+    [
+        35.0 / 384.0,
+        0.0,
+        500.0 / 1113.0,
+        125.0 / 192.0,
+        -2187.0 / 6784.0,
+        11.0 / 84.0,
+    ],
+];
+const C: [f64; 7] = [0.0, 1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0, 1.0];
+/// The difference between the weights of the fifth and the embedded fourth order solution.
+const ERROR_WEIGHTS: [f64; 7] = [
+    35.0 / 384.0 - 5179.0 / 57600.0,
+    0.0,
+    500.0 / 1113.0 - 7571.0 / 16695.0,
+    125.0 / 192.0 - 393.0 / 640.0,
+    -2187.0 / 6784.0 + 92097.0 / 339200.0,
+    11.0 / 84.0 - 187.0 / 2100.0,
+    -1.0 / 40.0,
+];
+
+/// The maximum number of steps of both methods, which bounds the number of samples.
+const MAX_STEPS: usize = 100_000;
+const RELATIVE_TOLERANCE: f64 = 1e-9;
+const ABSOLUTE_TOLERANCE: f64 = 1e-12;
+
+/// Solves `dy/dt = rhs` with `y(t0) = y0` from `t0` to `t1`, returning the `(t, y)` samples.
+///
+/// The right-hand side is evaluated with `t` and `y` bound to the current values.
+/// With a number of `steps` the classical Runge–Kutta method is used with a fixed step size,
+/// otherwise the Dormand–Prince method adapts the step size to the local error.
+pub(crate) fn odesolve(
+    rhs: &Expr,
+    y0: f64,
+    t0: f64,
+    t1: f64,
+    steps: Option<i32>,
+    vars: Option<&dyn Resolver>,
+) -> Result<Vec<(f64, f64)>, String> {
+    if !t0.is_finite() || !t1.is_finite() || !y0.is_finite() {
+        return Err("The initial value and the bounds of an ODE must be finite".to_string());
+    }
+    let names = ["t", "y"];
+    let mut function = MultiFunction::new(rhs, &names, vars);
+    let mut f = |t: f64, y: f64| function.eval(&[t, y]);
+    match steps {
+        Some(steps) if steps <= 0 => Err(format!(
+            "The number of steps must be positive, got {}",
+            steps
+        )),
+        Some(steps) if steps as usize > MAX_STEPS => Err(format!(
+            "The number of steps must be at most {}, got {}",
+            MAX_STEPS, steps
+        )),
+        Some(steps) => runge_kutta(&mut f, y0, t0, t1, steps as usize),
+        None => dormand_prince(&mut f, y0, t0, t1).map_err(|err| {
+            err.unwrap_or(format!(
+                "Solving dy/dt = {} did not converge from t = {} to {}",
+                rhs, t0, t1
+            ))
+        }),
+    }
+}
+
+fn runge_kutta(
+    f: &mut impl FnMut(f64, f64) -> Result<f64, String>,
+    y0: f64,
+    t0: f64,
+    t1: f64,
+    steps: usize,
+) -> Result<Vec<(f64, f64)>, String> {
+    let h = (t1 - t0) / steps as f64;
+    let mut samples = vec![(t0, y0)];
+    let mut y = y0;
+    for i in 0..steps {
+        let t = t0 + i as f64 * h;
+        let k1 = f(t, y)?;
+        let k2 = f(t + h / 2.0, y + h / 2.0 * k1)?;
+        let k3 = f(t + h / 2.0, y + h / 2.0 * k2)?;
+        let k4 = f(t + h, y + h * k3)?;
+        y += h / 6.0 * (k1 + 2.0 * k2 + 2.0 * k3 + k4);
+        // computing t from the index avoids accumulating rounding errors
+        let t = if i + 1 == steps {
+            t1
+        } else {
+            t0 + (i + 1) as f64 * h
+        };
+        samples.push((t, y));
+    }
+    Ok(samples)
+}
+
+/// Returns `None` as the error if the step size became too small.
+fn dormand_prince(
+    f: &mut impl FnMut(f64, f64) -> Result<f64, String>,
+    y0: f64,
+    t0: f64,
+    t1: f64,
+) -> Result<Vec<(f64, f64)>, Option<String>> {
+    let mut samples = vec![(t0, y0)];
+    if t0 == t1 {
+        return Ok(samples);
+    }
+    let (mut t, mut y) = (t0, y0);
+    let mut h = (t1 - t0) / 100.0;
+    let mut k = [0.0; 7];
+    k[0] = f(t, y).map_err(Some)?;
+    for _ in 0..MAX_STEPS {
+        if (t1 - t - h) * h.signum() < 0.0 {
+            h = t1 - t;
+        }
+        for stage in 1..7 {
+            let slope: f64 = (0..stage).map(|j| A[stage - 1][j] * k[j]).sum();
+            k[stage] = f(t + C[stage] * h, y + h * slope).map_err(Some)?;
+        }
+        // the last stage is evaluated at the fifth order solution
+        let next = y + h * (0..6).map(|j| A[5][j] * k[j]).sum::<f64>();
+        let error = (h * (0..7).map(|j| ERROR_WEIGHTS[j] * k[j]).sum::<f64>()).abs();
+        let tolerance = ABSOLUTE_TOLERANCE + RELATIVE_TOLERANCE * y.abs().max(next.abs());
+        if !next.is_finite() || !error.is_finite() {
+            return Err(None);
+        }
+        if error <= tolerance {
+            t = if h == t1 - t { t1 } else { t + h };
+            y = next;
+            samples.push((t, y));
+            if t == t1 {
+                return Ok(samples);
+            }
+            // first same as last, the slope at the new point is the last stage
+            k[0] = k[6];
+        }
+        let factor = 0.9 * (tolerance / error).powf(0.2);
+        h *= factor.clamp(0.2, 5.0);
+        if h.abs() <= 1e-12 * (1.0 + t.abs()) {
+            return Err(None);
+        }
+    }
+    Err(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve_str(
+        rhs: &str,
+        y0: f64,
+        t1: f64,
+        steps: Option<i32>,
+    ) -> Result<Vec<(f64, f64)>, String> {
+        odesolve(&Expr::parse(rhs)?, y0, 0.0, t1, steps, None)
+    }
+
+    #[test]
+    fn fixed_steps() -> Result<(), String> {
+        let samples = solve_str("y", 1.0, 1.0, Some(100))?;
+        assert_eq!(samples.len(), 101);
+        assert_eq!(samples[100].0, 1.0);
+        assert!((samples[100].1 - std::f64::consts::E).abs() < 1e-9);
+        assert!(solve_str("y", 1.0, 1.0, Some(0)).is_err());
+        assert_eq!(
+            solve_str("y", 1.0, 1.0, Some(2_000_000_000)),
+            Err("The number of steps must be at most 100000, got 2000000000".to_string())
+        );
+        assert_eq!(solve_str("y", 0.0, 1.0, Some(100_000))?.len(), 100_001);
+        Ok(())
+    }
+
+    #[test]
+    fn adaptive_steps() -> Result<(), String> {
+        let samples = solve_str("-2 * t * y", 1.0, 2.0, None)?;
+        let (t, y) = samples[samples.len() - 1];
+        assert_eq!(t, 2.0);
+        assert!((y - (-4f64).exp()).abs() < 1e-9);
+        assert!(samples.len() < 100);
+
+        let backwards = solve_str("cos(t)", 0.0, -3.0, None)?;
+        let (_, y) = backwards[backwards.len() - 1];
+        assert!((y - (-3f64).sin()).abs() < 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn blow_up() {
+        // y = 1 / (1 - t) blows up at t = 1
+        assert!(solve_str("y^2", 1.0, 2.0, None).is_err());
+        assert!(solve_str("sqrt(y)", -1.0, 1.0, None).is_err());
+    }
+}
@@ -0,0 +1,248 @@
+use crate::expr::Expr;
+use crate::numeric::{MultiFunction, RealFunction};
+use crate::resolver::Resolver;
+
+const MAX_ITERATIONS: usize = 500;
+
+/// Finds the minimum of `expr` over `var` in `[lo .. hi]` with Brent's method,
+/// returning the argmin and the value there. Maxima are found by negating the expression if `maximize` is set.
+pub(crate) fn optimize(
+    expr: &Expr,
+    var: &str,
+    lo: f64,
+    hi: f64,
+    maximize: bool,
+    vars: Option<&dyn Resolver>,
+) -> Result<(f64, f64), String> {
+    if lo >= hi || !lo.is_finite() || !hi.is_finite() {
+        return Err(format!(
+            "Unable to optimize in the range [{} .. {}]",
+            lo, hi
+        ));
+    }
+    let sign = if maximize { -1.0 } else { 1.0 };
+    let mut function = RealFunction::new(expr, var, vars);
+    let mut f = |x: f64| Ok::<f64, String>(sign * function.eval(x)?);
+
+    let golden = (3.0 - 5f64.sqrt()) / 2.0;
+    let tolerance = f64::EPSILON.sqrt();
+    let (mut a, mut b) = (lo, hi);
+    let mut x = a + golden * (b - a);
+    let (mut w, mut v) = (x, x);
+    let mut fx = f(x)?;
+    let (mut fw, mut fv) = (fx, fx);
+    // the last two steps, used to decide whether the parabolic interpolation makes progress
+    let (mut d, mut e) = (0.0f64, 0.0f64);
+    let mut converged = false;
+    for _ in 0..MAX_ITERATIONS {
+        let middle = a / 2.0 + b / 2.0;
+        let tol1 = tolerance * x.abs() + 1e-12;
+        let tol2 = 2.0 * tol1;
+        if (x - middle).abs() <= tol2 - (b - a) / 2.0 {
+            converged = true;
+            break;
+        }
+        let mut golden_step = true;
+        if e.abs() > tol1 {
+            // fit a parabola through x, w and v
+            let r = (x - w) * (fx - fv);
+            let q = (x - v) * (fx - fw);
+            let mut p = (x - v) * q - (x - w) * r;
+            let mut q = 2.0 * (q - r);
+            if q > 0.0 {
+                p = -p;
+            }
+            q = q.abs();
+            if p.abs() < (0.5 * q * e).abs() && p > q * (a - x) && p < q * (b - x) {
+                e = d;
+                d = p / q;
+                let u = x + d;
+                if u - a < tol2 || b - u < tol2 {
+                    d = tol1.copysign(middle - x);
+                }
+                golden_step = false;
+            }
+        }
+        if golden_step {
+            e = if x >= middle { a - x } else { b - x };
+            d = golden * e;
+        }
+        let u = if d.abs() >= tol1 {
+            x + d
+        } else {
+            x + tol1.copysign(d)
+        };
+        let fu = f(u)?;
+        if fu <= fx {
+            if u >= x {
+                a = x;
+            } else {
+                b = x;
+            }
+            (v, fv, w, fw, x, fx) = (w, fw, x, fx, u, fu);
+        } else {
+            if u < x {
+                a = u;
+            } else {
+                b = u;
+            }
+            if fu <= fw || w == x {
+                (v, fv, w, fw) = (w, fw, u, fu);
+            } else if fu <= fv || v == x || v == w {
+                (v, fv) = (u, fu);
+            }
+        }
+    }
+    if !converged || fx.is_nan() {
+        return Err(format!(
+            "Optimizing {} did not converge in [{} .. {}]",
+            expr, lo, hi
+        ));
+    }
+    // the interior search never evaluates the bounds themselves
+    for bound in [lo, hi] {
+        let f_bound = f(bound)?;
+        if f_bound < fx {
+            (x, fx) = (bound, f_bound);
+        }
+    }
+    Ok((x, sign * fx))
+}
+
+/// Finds a local minimum of `expr` over the variables `names` with the Nelder–Mead method, starting at `start`,
+/// returning the argmin and the value there. Maxima are found by negating the expression if `maximize` is set.
+pub(crate) fn optimize_multi(
+    expr: &Expr,
+    names: &[&str],
+    start: &[f64],
+    maximize: bool,
+    vars: Option<&dyn Resolver>,
+) -> Result<(Vec<f64>, f64), String> {
+    if names.len() != start.len() || names.is_empty() {
+        return Err(format!(
+            "Expected a starting point for each of the {} variable(s) but got {}",
+            names.len(),
+            start.len()
+        ));
+    }
+    let sign = if maximize { -1.0 } else { 1.0 };
+    let mut function = MultiFunction::new(expr, names, vars);
+    let mut f = |point: &[f64]| Ok::<f64, String>(sign * function.eval(point)?);
+
+    let n = start.len();
+    let mut simplex = vec![(start.to_vec(), f(start)?)];
+    for i in 0..n {
+        let mut point = start.to_vec();
+        point[i] = if point[i] == 0.0 {
+            0.00025
+        } else {
+            point[i] * 1.05
+        };
+        let value = f(&point)?;
+        simplex.push((point, value));
+    }
+    // moves `point` along the line from the centroid, e.g. reflects it for `t = -1`
+    let along = |centroid: &[f64], point: &[f64], t: f64| -> Vec<f64> {
+        centroid
+            .iter()
+            .zip(point)
+            .map(|(c, p)| c + t * (p - c))
+            .collect()
+    };
+
+    for _ in 0..MAX_ITERATIONS * n {
+        simplex.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+        let (best, worst) = (&simplex[0], &simplex[n]);
+        let size = simplex[1..]
+            .iter()
+            .flat_map(|(point, _)| point.iter().zip(&best.0).map(|(p, b)| (p - b).abs()))
+            .fold(0.0, f64::max);
+        if (worst.1 - best.1).abs() <= 1e-12 * (1.0 + best.1.abs())
+            && size <= 1e-9 * (1.0 + best.0.iter().fold(0.0, |m, x| x.abs().max(m)))
+        {
+            let (point, value) = simplex.swap_remove(0);
+            return Ok((point, sign * value));
+        }
+
+        let centroid: Vec<f64> = (0..n)
+            .map(|i| simplex[..n].iter().map(|(point, _)| point[i]).sum::<f64>() / n as f64)
+            .collect();
+        let worst = simplex[n].clone();
+        let reflected = along(&centroid, &worst.0, -1.0);
+        let f_reflected = f(&reflected)?;
+        if f_reflected < simplex[0].1 {
+            let expanded = along(&centroid, &worst.0, -2.0);
+            let f_expanded = f(&expanded)?;
+            simplex[n] = if f_expanded < f_reflected {
+                (expanded, f_expanded)
+            } else {
+                (reflected, f_reflected)
+            };
+        } else if f_reflected < simplex[n - 1].1 {
+            simplex[n] = (reflected, f_reflected);
+        } else {
+            let contracted = if f_reflected < worst.1 {
+                along(&centroid, &worst.0, -0.5)
+            } else {
+                along(&centroid, &worst.0, 0.5)
+            };
+            let f_contracted = f(&contracted)?;
+            if f_contracted < worst.1.min(f_reflected) {
+                simplex[n] = (contracted, f_contracted);
+            } else {
+                // shrink towards the best point
+                let best = simplex[0].0.clone();
+                for (point, value) in simplex[1..].iter_mut() {
+                    *point = along(&best, point, 0.5);
+                    *value = f(point)?;
+                }
+            }
+        }
+    }
+    Err(format!("Optimizing {} did not converge", expr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= 1e-6 * (1.0 + b.abs()), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn one_dimension() -> Result<(), String> {
+        let expr = Expr::parse("(x - 2)^2 + 1")?;
+        let (x, value) = optimize(&expr, "x", -10.0, 10.0, false, None)?;
+        assert_close(x, 2.0);
+        assert_close(value, 1.0);
+
+        let (x, value) = optimize(&Expr::parse("sin(x)")?, "x", 0.0, 3.0, true, None)?;
+        assert_close(x, std::f64::consts::FRAC_PI_2);
+        assert_close(value, 1.0);
+
+        // monotone functions are optimal at a bound
+        let (x, _) = optimize(&Expr::parse("exp(x)")?, "x", 0.0, 1.0, false, None)?;
+        assert_eq!(x, 0.0);
+        assert!(optimize(&expr, "x", 1.0, 1.0, false, None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn several_dimensions() -> Result<(), String> {
+        let rosenbrock = Expr::parse("(1 - x)^2 + 100 * (y - x^2)^2")?;
+        let (point, value) = optimize_multi(&rosenbrock, &["x", "y"], &[-1.2, 1.0], false, None)?;
+        assert_close(point[0], 1.0);
+        assert_close(point[1], 1.0);
+        assert_close(value, 0.0);
+
+        let expr = Expr::parse("-(x - 1)^2 - (y + 2)^2 + 3")?;
+        let (point, value) = optimize_multi(&expr, &["x", "y"], &[0.0, 0.0], true, None)?;
+        assert_close(point[0], 1.0);
+        assert_close(point[1], -2.0);
+        assert_close(value, 3.0);
+
+        assert!(optimize_multi(&expr, &["x", "y"], &[0.0], false, None).is_err());
+        Ok(())
+    }
+}
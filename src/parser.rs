@@ -0,0 +1,550 @@
+use std::fmt;
+
+use crate::expr::{Expr, Function, Op};
+use crate::type_enum::Type;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Operand(String),
+    Symbol(char),
+    DotDot,
+    /// A quoted string literal like `"HOME"`, without the quotes and escapes
+    Text(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Operand(operand) => write!(f, "{}", operand),
+            Token::Symbol(symbol) => write!(f, "{}", symbol),
+            Token::DotDot => write!(f, ".."),
+            Token::Text(text) => write!(f, "\"{}\"", text),
+        }
+    }
+}
+
+/// Parses an equation into an `Expr`.
+pub(crate) fn parse(equation: &str) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(equation)?,
+        pos: 0,
+    };
+    if parser.tokens.is_empty() {
+        return Err("Error: Empty equation".to_string());
+    }
+    let expr = parser.parse_equation()?;
+    match parser.next() {
+        None => Ok(expr),
+        Some(Token::Symbol(')')) => Err(String::from(
+            "parentheses must be opened before being closed",
+        )),
+        Some(Token::Symbol(']')) => {
+            Err(String::from("brackets must be opened before being closed"))
+        }
+        Some(token) => Err(format!("Unexpected '{}' after {}", token, expr)),
+    }
+}
+
+/// Splits an equation into operands and symbols.
+///
+/// Commas only separate arguments inside the parentheses of a function call and the brackets of a list,
+/// everywhere else they are part of the operand, e.g. the decimal commas in `1,5` and `[1,5 .. 2]`.
+/// Empty parentheses that are not part of a function call are dropped.
+/// Quoted strings are single tokens, in which `\"` and `\\` escape a quote and a backslash.
+fn tokenize(equation: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut operand = String::new();
+    // for each open parenthesis or bracket, whether commas separate arguments within it
+    let mut separates_args: Vec<bool> = Vec::new();
+    let mut chars = equation.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '.' if chars.peek() == Some(&'.') => {
+                chars.next();
+                Some(Token::DotDot)
+            }
+            ',' if separates_args.last() == Some(&true) => Some(Token::Symbol(c)),
+            '(' => {
+                let is_call =
+                    !operand.is_empty() || matches!(tokens.last(), Some(Token::Operand(_)));
+                separates_args.push(is_call);
+                Some(Token::Symbol(c))
+            }
+            '[' => {
+                separates_args.push(!is_interval(chars.clone()));
+                Some(Token::Symbol(c))
+            }
+            ')' => {
+                let is_call = separates_args.pop().unwrap_or(false);
+                if operand.is_empty() && !is_call && tokens.last() == Some(&Token::Symbol('(')) {
+                    tokens.pop();
+                    continue;
+                }
+                Some(Token::Symbol(c))
+            }
+            ']' => {
+                separates_args.pop();
+                Some(Token::Symbol(c))
+            }
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => text.push(c),
+                            Some(c) => {
+                                text.push('\\');
+                                text.push(c);
+                            }
+                            None => text.push('\\'),
+                        },
+                        Some(c) => text.push(c),
+                        None => return Err("All strings must be closed!".to_string()),
+                    }
+                }
+                Some(Token::Text(text))
+            }
+            '+' | '-' | '*' | '/' | '%' | '^' | '±' | '=' | '!' => Some(Token::Symbol(c)),
+            c if c.is_whitespace() => None,
+            c => {
+                operand.push(c);
+                continue;
+            }
+        };
+        if !operand.is_empty() {
+            tokens.push(Token::Operand(std::mem::take(&mut operand)));
+        }
+        tokens.extend(token);
+    }
+    if !operand.is_empty() {
+        tokens.push(Token::Operand(operand));
+    }
+    Ok(tokens)
+}
+
+/// Returns `true` if the bracket before `chars` contains `..` outside of nested parentheses, brackets and strings,
+/// so it is an interval and not a list.
+fn is_interval(mut chars: impl Iterator<Item = char>) -> bool {
+    let mut depth = 0;
+    let mut previous = None;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => {
+                            chars.next();
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            '(' | '[' => depth += 1,
+            ')' | ']' if depth == 0 => return false,
+            ')' | ']' => depth -= 1,
+            '.' if depth == 0 && previous == Some('.') => return true,
+            _ => (),
+        }
+        previous = Some(c);
+    }
+    false
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn next_is(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect_closing(&mut self, symbol: char) -> Result<(), String> {
+        match self.next() {
+            Some(Token::Symbol(c)) if c == symbol => Ok(()),
+            None if symbol == ')' => Err("All parentheses must be closed!".to_string()),
+            None => Err("All brackets must be closed!".to_string()),
+            Some(token) => Err(format!("Expected '{}' but found '{}'", symbol, token)),
+        }
+    }
+
+    /// `equation := sum ('=' sum)?`
+    fn parse_equation(&mut self) -> Result<Expr, String> {
+        let lhs = self.parse_sum()?;
+        if self.next_is('=') {
+            return Ok(Expr::BinOp(
+                Op::Eq,
+                Box::new(lhs),
+                Box::new(self.parse_sum()?),
+            ));
+        }
+        Ok(lhs)
+    }
+
+    /// `sum := product (('+' | '-') product)*`
+    fn parse_sum(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_product()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Symbol('+')) => Op::Add,
+                Some(Token::Symbol('-')) => Op::Sub,
+                _ => return Ok(expr),
+            };
+            self.pos += 1;
+            expr = Expr::BinOp(op, Box::new(expr), Box::new(self.parse_product()?));
+        }
+    }
+
+    /// `product := unary (('*' | '/' | '%') unary)*`
+    fn parse_product(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Symbol('*')) => Op::Mul,
+                Some(Token::Symbol('/')) => Op::Div,
+                Some(Token::Symbol('%')) => Op::Rem,
+                _ => return Ok(expr),
+            };
+            self.pos += 1;
+            expr = Expr::BinOp(op, Box::new(expr), Box::new(self.parse_unary()?));
+        }
+    }
+
+    /// `unary := '-' unary | power`
+    ///
+    /// Negated numbers are folded into negative literals.
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        if self.next_is('-') {
+            return Ok(match self.parse_unary()? {
+                Expr::Value(value @ (Type::Int(_) | Type::Float(_))) => Expr::Value((-value)?),
+                expr => Expr::Neg(Box::new(expr)),
+            });
+        }
+        self.parse_power()
+    }
+
+    /// `power := plus_minus ('^' unary)?`
+    fn parse_power(&mut self) -> Result<Expr, String> {
+        let base = self.parse_plus_minus()?;
+        if self.next_is('^') {
+            return Ok(Expr::BinOp(
+                Op::Pow,
+                Box::new(base),
+                Box::new(self.parse_unary()?),
+            ));
+        }
+        Ok(base)
+    }
+
+    /// `plus_minus := factorial ('±' factorial)?`
+    fn parse_plus_minus(&mut self) -> Result<Expr, String> {
+        let value = self.parse_factorial()?;
+        if self.next_is('±') {
+            return Ok(Expr::BinOp(
+                Op::PlusMinus,
+                Box::new(value),
+                Box::new(self.parse_factorial()?),
+            ));
+        }
+        Ok(value)
+    }
+
+    /// `factorial := primary '!'*`
+    fn parse_factorial(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_primary()?;
+        while self.next_is('!') {
+            expr = Expr::Factorial(Box::new(expr));
+        }
+        Ok(expr)
+    }
+
+    /// `primary := let | operand | text | name '(' arguments ')' | '(' sum ')' | '[' sum '..' sum ']' | '[' (sum (',' sum)*)? ']'`
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Operand(operand))
+                if operand == "let" && matches!(self.peek(), Some(Token::Operand(_))) =>
+            {
+                self.parse_let()
+            }
+            Some(Token::Operand(operand)) => {
+                if self.next_is('(') {
+                    return self.parse_call(operand);
+                }
+                Ok(operand_to_expr(operand))
+            }
+            Some(Token::Text(text)) => Ok(Expr::Value(Type::String(text))),
+            Some(Token::Symbol('(')) => {
+                let expr = self.parse_sum()?;
+                self.expect_closing(')')?;
+                Ok(expr)
+            }
+            Some(Token::Symbol('[')) => {
+                if self.next_is(']') {
+                    return Ok(Expr::List(Vec::new()));
+                }
+                let first = self.parse_sum()?;
+                if self.peek() == Some(&Token::DotDot) {
+                    self.pos += 1;
+                    let hi = self.parse_sum()?;
+                    self.expect_closing(']')?;
+                    return Ok(Expr::Interval(Box::new(first), Box::new(hi)));
+                }
+                let mut items = vec![first];
+                while self.next_is(',') {
+                    items.push(self.parse_sum()?);
+                }
+                self.expect_closing(']')?;
+                Ok(Expr::List(items))
+            }
+            Some(Token::Symbol(')')) => Err(String::from(
+                "parentheses must be opened before being closed",
+            )),
+            Some(token) => Err(format!("Unexpected '{}'", token)),
+            None => Err("Unexpected end of equation".to_string()),
+        }
+    }
+
+    /// `let := 'let' name ('(' (name (',' name)*)? ')')? '=' sum 'in' sum`
+    ///
+    /// Binding parameters defines a function, e.g. `let f(x) = x^2 in f(3)`.
+    fn parse_let(&mut self) -> Result<Expr, String> {
+        let name = match self.next() {
+            Some(Token::Operand(name)) if is_name(&name) => name,
+            Some(token) => return Err(format!("{} is not a valid name to bind", token)),
+            None => return Err("let expects a name to bind".to_string()),
+        };
+        let params = if self.next_is('(') {
+            let mut params = Vec::new();
+            if !self.next_is(')') {
+                loop {
+                    match self.next() {
+                        Some(Token::Operand(param)) if is_name(&param) => params.push(param),
+                        Some(token) => {
+                            return Err(format!("Expected a parameter but found '{}'", token))
+                        }
+                        None => return Err("All parentheses must be closed!".to_string()),
+                    }
+                    if !self.next_is(',') {
+                        break;
+                    }
+                }
+                self.expect_closing(')')?;
+            }
+            Some(params)
+        } else {
+            None
+        };
+        if !self.next_is('=') {
+            return Err(format!("let {} is missing '='", name));
+        }
+        let value = self.parse_sum()?;
+        match self.next() {
+            Some(Token::Operand(keyword)) if keyword == "in" => (),
+            _ => return Err(format!("let {} is missing 'in'", name)),
+        }
+        let value = match params {
+            Some(params) => Expr::Value(Type::Function(Function::new(params, value))),
+            None => value,
+        };
+        Ok(Expr::Let(
+            name,
+            Box::new(value),
+            Box::new(self.parse_sum()?),
+        ))
+    }
+
+    fn parse_call(&mut self, name: String) -> Result<Expr, String> {
+        if !is_name(&name) {
+            return Err(format!("{} is not a valid function name", name));
+        }
+        let mut args = Vec::new();
+        if !self.next_is(')') {
+            loop {
+                args.push(self.parse_equation()?);
+                if !self.next_is(',') {
+                    break;
+                }
+            }
+            self.expect_closing(')')?;
+        }
+        Ok(Expr::Call(name, args))
+    }
+}
+
+/// Returns `true` if the operand can be the name of a variable or function.
+fn is_name(operand: &str) -> bool {
+    operand.starts_with(|c: char| c.is_alphabetic() || c == '_')
+}
+
+/// Turns an operand into a literal value, or a variable if it is not a number.
+fn operand_to_expr(operand: String) -> Expr {
+    match Type::from(operand.as_str()) {
+        Type::String(_) => Expr::Var(operand),
+        value => Expr::Value(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(name: &str) -> Box<Expr> {
+        Box::new(Expr::Var(name.to_string()))
+    }
+
+    fn int(value: i32) -> Box<Expr> {
+        Box::new(Expr::Value(Type::Int(value)))
+    }
+
+    #[test]
+    fn precedence() -> Result<(), String> {
+        assert_eq!(
+            parse("1 - 2 * x ^ 2")?,
+            Expr::BinOp(
+                Op::Sub,
+                int(1),
+                Box::new(Expr::BinOp(
+                    Op::Mul,
+                    int(2),
+                    Box::new(Expr::BinOp(Op::Pow, var("x"), int(2)))
+                ))
+            )
+        );
+        assert_eq!(
+            parse("-x ^ 2")?,
+            Expr::Neg(Box::new(Expr::BinOp(Op::Pow, var("x"), int(2))))
+        );
+        assert_eq!(
+            parse("2 ^ x!")?,
+            Expr::BinOp(Op::Pow, int(2), Box::new(Expr::Factorial(var("x"))))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn calls_and_commas() -> Result<(), String> {
+        assert_eq!(
+            parse("f(1,5, x)")?,
+            Expr::Call("f".to_string(), vec![*int(1), *int(5), *var("x")])
+        );
+        assert_eq!(parse("(1,5)")?, Expr::Value(Type::Float(1.5)));
+        assert_eq!(
+            parse("[1,5, x]")?,
+            Expr::List(vec![*int(1), *int(5), *var("x")])
+        );
+        assert_eq!(parse("[]")?, Expr::List(Vec::new()));
+        assert_eq!(
+            parse("[1,5 .. 2]")?,
+            Expr::Interval(Box::new(Expr::Value(Type::Float(1.5))), int(2))
+        );
+        assert_eq!(
+            parse("[max(1, 2) .. 3]")?,
+            Expr::Interval(
+                Box::new(Expr::Call("max".to_string(), vec![*int(1), *int(2)])),
+                int(3)
+            )
+        );
+        assert_eq!(
+            parse("[[1,5 .. 2], 3]")?,
+            Expr::List(vec![
+                Expr::Interval(Box::new(Expr::Value(Type::Float(1.5))), int(2)),
+                *int(3)
+            ])
+        );
+        assert_eq!(
+            parse("[\"..\", 1,5]")?,
+            Expr::List(vec![
+                Expr::Value(Type::String("..".to_string())),
+                *int(1),
+                *int(5)
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn string_literals() -> Result<(), String> {
+        let text = |text: &str| Expr::Value(Type::String(text.to_string()));
+        assert_eq!(
+            parse("env(\"NOPE\", \"a b\")")?,
+            Expr::Call("env".to_string(), vec![text("NOPE"), text("a b")])
+        );
+        assert_eq!(
+            parse("env(\"MY-VAR\")")?,
+            Expr::Call("env".to_string(), vec![text("MY-VAR")])
+        );
+        assert_eq!(
+            parse("x+\"(1, 2) * \\\"3\\\" \\\\ \\n\"")?,
+            Expr::BinOp(Op::Add, var("x"), Box::new(text("(1, 2) * \"3\" \\ \\n")))
+        );
+        assert_eq!(
+            parse("\"open"),
+            Err("All strings must be closed!".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse(""), Err("Error: Empty equation".to_string()));
+        assert_eq!(
+            parse("(1 + 2"),
+            Err("All parentheses must be closed!".to_string())
+        );
+        assert_eq!(
+            parse("1 + 2)"),
+            Err("parentheses must be opened before being closed".to_string())
+        );
+        assert!(parse("1 + * 2").is_err());
+        assert!(parse("2(3)").is_err());
+        assert!(parse("!3").is_err());
+        assert_eq!(parse("let x = 2"), Err("let x is missing 'in'".to_string()));
+        assert!(parse("let f(1) = 2 in f(1)").is_err());
+    }
+
+    #[test]
+    fn let_bindings() -> Result<(), String> {
+        assert_eq!(
+            parse("let x = 2 in x * x")?,
+            Expr::Let(
+                "x".to_string(),
+                int(2),
+                Box::new(Expr::BinOp(Op::Mul, var("x"), var("x")))
+            )
+        );
+        assert_eq!(
+            parse("let f(x, y) = x in f")?,
+            Expr::Let(
+                "f".to_string(),
+                Box::new(Expr::Value(Type::Function(Function::new(
+                    vec!["x".to_string(), "y".to_string()],
+                    *var("x")
+                )))),
+                var("f")
+            )
+        );
+        assert_eq!(
+            parse("let(2)")?,
+            Expr::Call("let".to_string(), vec![*int(2)])
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,403 @@
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+const MAX_ITERATIONS: usize = 1_000;
+/// The maximum degree of a power of a polynomial.
+const MAX_DEGREE: usize = 1_000;
+
+/// Represents a polynomial `c_n x^n + ... + c_1 x + c_0` with real coefficients.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::polynomial::Polynomial;
+///
+/// let p = Polynomial::new(vec![1.0, -3.0, 2.0]);
+/// let q = Polynomial::new(vec![1.0, -1.0]);
+///
+/// assert_eq!(p.to_string(), "x^2 - 3x + 2");
+/// assert_eq!(p.eval(4.0), 6.0);
+/// assert_eq!(p.div_rem(&q).unwrap(), (Polynomial::new(vec![1.0, -2.0]), Polynomial::zero()));
+/// assert_eq!(p.roots().unwrap(), vec![(1.0, 0.0), (2.0, 0.0)]);
+/// ```
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct Polynomial {
+    /// The coefficients from the constant term upwards, without trailing zeros.
+    coefficients: Vec<f64>,
+}
+
+impl Polynomial {
+    /// Creates a polynomial from its coefficients, starting with the one of the highest power.
+    pub fn new(mut coefficients: Vec<f64>) -> Polynomial {
+        coefficients.reverse();
+        Polynomial::from_ascending(coefficients)
+    }
+
+    /// Creates the polynomial `0`.
+    pub fn zero() -> Polynomial {
+        Polynomial {
+            coefficients: Vec::new(),
+        }
+    }
+
+    /// Creates the constant polynomial `c`.
+    pub fn constant(c: f64) -> Polynomial {
+        Polynomial::from_ascending(vec![c])
+    }
+
+    /// Creates the polynomial `x`.
+    pub fn x() -> Polynomial {
+        Polynomial::from_ascending(vec![0.0, 1.0])
+    }
+
+    fn from_ascending(mut coefficients: Vec<f64>) -> Polynomial {
+        while coefficients.last() == Some(&0.0) {
+            coefficients.pop();
+        }
+        Polynomial { coefficients }
+    }
+
+    /// Returns the coefficients, starting with the one of the highest power.
+    pub fn coefficients(&self) -> Vec<f64> {
+        self.coefficients.iter().rev().copied().collect()
+    }
+
+    /// Returns the degree of the polynomial, which is `0` for constants including `0`.
+    pub fn degree(&self) -> usize {
+        self.coefficients.len().saturating_sub(1)
+    }
+
+    /// Returns `true` if this is the polynomial `0`.
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    /// Evaluates the polynomial at `x` using Horner's method.
+    pub fn eval(&self, x: f64) -> f64 {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(0.0, |acc, c| acc * x + c)
+    }
+
+    /// Returns the derivative of the polynomial.
+    pub fn derivative(&self) -> Polynomial {
+        Polynomial::from_ascending(
+            self.coefficients
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(power, c)| power as f64 * c)
+                .collect(),
+        )
+    }
+
+    /// Divides the polynomial by `divisor`, returning the quotient and the remainder.
+    ///
+    /// Returns an error if the divisor is the polynomial `0`.
+    pub fn div_rem(&self, divisor: &Polynomial) -> Result<(Polynomial, Polynomial), String> {
+        let Some(&leading) = divisor.coefficients.last() else {
+            return Err("Unable to divide by zero".to_string());
+        };
+        let mut remainder = self.coefficients.clone();
+        if remainder.len() < divisor.coefficients.len() {
+            return Ok((Polynomial::zero(), self.clone()));
+        }
+        let mut quotient = vec![0.0; remainder.len() - divisor.coefficients.len() + 1];
+        for power in (0..quotient.len()).rev() {
+            let factor = remainder[power + divisor.degree()] / leading;
+            quotient[power] = factor;
+            for (i, c) in divisor.coefficients.iter().enumerate() {
+                remainder[power + i] -= factor * c;
+            }
+            // the leading term cancels exactly, even if rounding says otherwise
+            remainder[power + divisor.degree()] = 0.0;
+        }
+        Ok((
+            Polynomial::from_ascending(quotient),
+            Polynomial::from_ascending(remainder),
+        ))
+    }
+
+    /// Returns all complex roots `(re, im)` of the polynomial, repeated according to their multiplicity.
+    ///
+    /// The roots are found with the Durand–Kerner method and sorted by their real part.
+    /// Returns an error for the polynomial `0`, which every number is a root of.
+    pub fn roots(&self) -> Result<Vec<(f64, f64)>, String> {
+        let Some(&leading) = self.coefficients.last() else {
+            return Err("Every number is a root of the zero polynomial".to_string());
+        };
+        let monic: Vec<Complex> = self
+            .coefficients
+            .iter()
+            .map(|c| Complex(c / leading, 0.0))
+            .collect();
+        let eval = |z: Complex| {
+            monic
+                .iter()
+                .rev()
+                .fold(Complex(0.0, 0.0), |acc, c| acc.mul(z).add(*c))
+        };
+
+        let seed = Complex(0.4, 0.9);
+        let mut roots = vec![seed; self.degree()];
+        for i in 1..roots.len() {
+            roots[i] = roots[i - 1].mul(seed);
+        }
+        for _ in 0..MAX_ITERATIONS {
+            let mut change: f64 = 0.0;
+            for i in 0..roots.len() {
+                let denominator = (0..roots.len())
+                    .filter(|j| *j != i)
+                    .fold(Complex(1.0, 0.0), |acc, j| acc.mul(roots[i].sub(roots[j])));
+                let step = eval(roots[i]).div(denominator);
+                if step.0.is_finite() && step.1.is_finite() {
+                    roots[i] = roots[i].sub(step);
+                    change = change.max(step.abs() / (1.0 + roots[i].abs()));
+                }
+            }
+            if change <= 1e-15 {
+                break;
+            }
+        }
+
+        let mut roots: Vec<(f64, f64)> = roots
+            .into_iter()
+            .map(|Complex(re, im)| {
+                // snap roots that are real up to rounding onto the real axis
+                let im = if im.abs() <= 1e-10 * (1.0 + re.abs()) {
+                    0.0
+                } else {
+                    im
+                };
+                (re, im)
+            })
+            .collect();
+        roots.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        Ok(roots)
+    }
+
+    /// Raises the polynomial to the power of `n` by repeated squaring.
+    pub(crate) fn powi(&self, n: u32) -> Result<Polynomial, String> {
+        if self.degree() > 0 && n as usize > MAX_DEGREE / self.degree() {
+            return Err(format!(
+                "Unable to raise a polynomial to a degree above {}",
+                MAX_DEGREE
+            ));
+        }
+        let mut result = Polynomial::constant(1.0);
+        let mut base = self.clone();
+        let mut n = n;
+        while n > 0 {
+            if n % 2 == 1 {
+                result = result * base.clone();
+            }
+            n /= 2;
+            if n > 0 {
+                base = base.clone() * base;
+            }
+        }
+        Ok(result)
+    }
+
+    pub(crate) fn scale(self, factor: f64) -> Polynomial {
+        Polynomial::from_ascending(self.coefficients.iter().map(|c| c * factor).collect())
+    }
+}
+
+/// A complex number `re + im i`, only used for finding roots.
+#[derive(Debug, Clone, Copy)]
+struct Complex(f64, f64);
+
+impl Complex {
+    fn add(self, other: Complex) -> Complex {
+        Complex(self.0 + other.0, self.1 + other.1)
+    }
+
+    fn sub(self, other: Complex) -> Complex {
+        Complex(self.0 - other.0, self.1 - other.1)
+    }
+
+    fn mul(self, other: Complex) -> Complex {
+        Complex(
+            self.0 * other.0 - self.1 * other.1,
+            self.0 * other.1 + self.1 * other.0,
+        )
+    }
+
+    fn div(self, other: Complex) -> Complex {
+        let norm = other.0 * other.0 + other.1 * other.1;
+        Complex(
+            (self.0 * other.0 + self.1 * other.1) / norm,
+            (self.1 * other.0 - self.0 * other.1) / norm,
+        )
+    }
+
+    fn abs(self) -> f64 {
+        self.0.hypot(self.1)
+    }
+}
+
+impl fmt::Display for Polynomial {
+    /// Prints the polynomial in `x` starting with the highest power, e.g. `x^2 - 3x + 2`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let mut first = true;
+        for (power, c) in self.coefficients.iter().enumerate().rev() {
+            if *c == 0.0 {
+                continue;
+            }
+            if first {
+                if *c < 0.0 {
+                    write!(f, "-")?;
+                }
+            } else if *c < 0.0 {
+                write!(f, " - ")?;
+            } else {
+                write!(f, " + ")?;
+            }
+            first = false;
+            if c.abs() != 1.0 || power == 0 {
+                write!(f, "{}", c.abs())?;
+            }
+            match power {
+                0 => (),
+                1 => write!(f, "x")?,
+                _ => write!(f, "x^{}", power)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Neg for Polynomial {
+    type Output = Polynomial;
+
+    fn neg(self) -> Polynomial {
+        self.scale(-1.0)
+    }
+}
+
+impl Add for Polynomial {
+    type Output = Polynomial;
+
+    fn add(self, other: Polynomial) -> Polynomial {
+        let (mut long, short) = if self.coefficients.len() >= other.coefficients.len() {
+            (self.coefficients, other.coefficients)
+        } else {
+            (other.coefficients, self.coefficients)
+        };
+        for (a, b) in long.iter_mut().zip(short) {
+            *a += b;
+        }
+        Polynomial::from_ascending(long)
+    }
+}
+
+impl Sub for Polynomial {
+    type Output = Polynomial;
+
+    fn sub(self, other: Polynomial) -> Polynomial {
+        self + -other
+    }
+}
+
+impl Mul for Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, other: Polynomial) -> Polynomial {
+        if self.is_zero() || other.is_zero() {
+            return Polynomial::zero();
+        }
+        let mut product = vec![0.0; self.coefficients.len() + other.coefficients.len() - 1];
+        for (i, a) in self.coefficients.iter().enumerate() {
+            for (j, b) in other.coefficients.iter().enumerate() {
+                product[i + j] += a * b;
+            }
+        }
+        Polynomial::from_ascending(product)
+    }
+}
+
+impl Div for Polynomial {
+    type Output = Result<Polynomial, String>;
+
+    /// Returns the quotient of the polynomial division, see `div_rem`.
+    fn div(self, other: Polynomial) -> Self::Output {
+        Ok(self.div_rem(&other)?.0)
+    }
+}
+
+impl Rem for Polynomial {
+    type Output = Result<Polynomial, String>;
+
+    /// Returns the remainder of the polynomial division, see `div_rem`.
+    fn rem(self, other: Polynomial) -> Self::Output {
+        if other.is_zero() {
+            return Err("Unable to calculate the remainder with a divisor of zero".to_string());
+        }
+        Ok(self.div_rem(&other)?.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poly(coefficients: &[f64]) -> Polynomial {
+        Polynomial::new(coefficients.to_vec())
+    }
+
+    #[test]
+    fn arithmetic() -> Result<(), String> {
+        let p = poly(&[1.0, -3.0, 2.0]);
+        assert_eq!(p.clone() + poly(&[-1.0, 3.0, 0.0]), poly(&[2.0]));
+        assert_eq!(p.clone() - p.clone(), Polynomial::zero());
+        assert_eq!(
+            poly(&[1.0, 1.0]) * poly(&[1.0, -1.0]),
+            poly(&[1.0, 0.0, -1.0])
+        );
+        assert_eq!(
+            poly(&[1.0, 0.0, 0.0, 1.0]).div_rem(&poly(&[1.0, 0.0, 1.0]))?,
+            (poly(&[1.0, 0.0]), poly(&[-1.0, 1.0]))
+        );
+        assert!((p.clone() / Polynomial::zero()).is_err());
+        assert_eq!(p.derivative(), poly(&[2.0, -3.0]));
+        assert_eq!(poly(&[1.0, 1.0]).powi(2)?, poly(&[1.0, 2.0, 1.0]));
+        assert_eq!(
+            poly(&[1.0, -1.0]).powi(5)?,
+            poly(&[1.0, -5.0, 10.0, -10.0, 5.0, -1.0])
+        );
+        assert_eq!(poly(&[2.0]).powi(10)?, poly(&[1024.0]));
+        assert_eq!(poly(&[1.0, 0.0]).powi(1_000)?.degree(), 1_000);
+        assert!(poly(&[1.0, 1.0]).powi(100_000_000).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(poly(&[1.0, -3.0, 2.0]).to_string(), "x^2 - 3x + 2");
+        assert_eq!(poly(&[-1.0, 0.0, 0.5, -1.0]).to_string(), "-x^3 + 0.5x - 1");
+        assert_eq!(poly(&[0.0, 0.0]).to_string(), "0");
+        assert_eq!(poly(&[-4.0]).to_string(), "-4");
+    }
+
+    #[test]
+    fn roots() -> Result<(), String> {
+        assert_eq!(
+            poly(&[2.0, -6.0, 4.0]).roots()?,
+            vec![(1.0, 0.0), (2.0, 0.0)]
+        );
+
+        let roots = poly(&[1.0, 0.0, 1.0]).roots()?;
+        assert_eq!(roots.len(), 2);
+        assert!(roots[0].0.abs() < 1e-12 && (roots[0].1 + 1.0).abs() < 1e-12);
+        assert!(roots[1].0.abs() < 1e-12 && (roots[1].1 - 1.0).abs() < 1e-12);
+
+        assert_eq!(poly(&[5.0]).roots()?, Vec::new());
+        assert!(Polynomial::zero().roots().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::f64::consts::TAU;
+use std::hash::{BuildHasher, Hasher};
+
+use crate::functions;
+use crate::type_enum::Type;
+
+thread_local! {
+    /// The state of the xoshiro256** generator of the current thread, randomly seeded unless `seed` is called.
+    static STATE: Cell<[u64; 4]> = Cell::new(state_from_seed(RandomState::new().build_hasher().finish()));
+}
+
+/// Seeds the random number generator of the current thread.
+pub(crate) fn seed(seed: u64) {
+    STATE.with(|state| state.set(state_from_seed(seed)));
+}
+
+/// Expands a seed into a state with SplitMix64, which never results in the invalid all-zero state.
+fn state_from_seed(mut seed: u64) -> [u64; 4] {
+    let mut next = || {
+        seed = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = seed;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+    [next(), next(), next(), next()]
+}
+
+fn next_u64() -> u64 {
+    STATE.with(|state| {
+        let mut s = state.get();
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        state.set(s);
+        result
+    })
+}
+
+/// Returns a uniformly distributed float in `[0, 1)`.
+fn next_f64() -> f64 {
+    (next_u64() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Returns a uniformly distributed integer in `[0, n)` by rejecting the values that would bias the result.
+fn below(n: u64) -> u64 {
+    let limit = u64::MAX - u64::MAX % n;
+    loop {
+        let value = next_u64();
+        if value < limit {
+            return value % n;
+        }
+    }
+}
+
+/// Returns `true` if `name` is a random function, whose calls result in a different value each time.
+pub(crate) fn is_random(name: &str) -> bool {
+    matches!(name, "rand" | "choice" | "randint" | "normal")
+}
+
+/// Calls the random function `name`, returning `None` if there is no such function.
+pub(crate) fn call(name: &str, args: &[Type]) -> Option<Result<Type, String>> {
+    let arity = match name {
+        "rand" => 0..=0,
+        "choice" => 1..=1,
+        "randint" | "normal" => 2..=2,
+        _ => return None,
+    };
+    Some(functions::check_arity(name, args, arity).and_then(|()| apply(name, args)))
+}
+
+fn apply(name: &str, args: &[Type]) -> Result<Type, String> {
+    match (name, args) {
+        ("rand", []) => Ok(Type::Float(next_f64())),
+        ("randint", [Type::Int(a), Type::Int(b)]) => {
+            if a > b {
+                return Err(format!("Unable to pick an integer between {} and {}", a, b));
+            }
+            let range = (*b as i64 - *a as i64 + 1) as u64;
+            Ok(Type::Int((*a as i64 + below(range) as i64) as i32))
+        }
+        ("normal", [mu, sigma]) => match (mu.as_f64(), sigma.as_f64()) {
+            (Some(_), Some(sigma)) if sigma < 0.0 => Err(format!(
+                "The standard deviation must not be negative, got {}",
+                sigma
+            )),
+            (Some(mu), Some(sigma)) => {
+                // Box–Muller transform, 1 - u avoids the logarithm of zero
+                let (u, v) = (1.0 - next_f64(), next_f64());
+                Ok(Type::Float(
+                    mu + sigma * (-2.0 * u.ln()).sqrt() * (TAU * v).cos(),
+                ))
+            }
+            _ => Err(format!(
+                "{} expects numbers but got {} and {}",
+                name,
+                mu.get_type(),
+                sigma.get_type()
+            )),
+        },
+        ("choice", [Type::List(values)]) => {
+            if values.is_empty() {
+                return Err("Unable to choose from an empty list".to_string());
+            }
+            Ok(values[below(values.len() as u64) as usize].clone())
+        }
+        ("choice", [value]) => Err(format!(
+            "{} expects a list but got {}",
+            name,
+            value.get_type()
+        )),
+        (name, args) => Err(format!(
+            "{} expects integers but got {}",
+            name,
+            args.iter()
+                .map(Type::get_type)
+                .collect::<Vec<&str>>()
+                .join(" and ")
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeding_is_deterministic() -> Result<(), String> {
+        seed(42);
+        let first: Vec<Type> = (0..5)
+            .map(|_| functions::call("rand", Vec::new()))
+            .collect::<Result<_, _>>()?;
+        seed(42);
+        let second: Vec<Type> = (0..5)
+            .map(|_| functions::call("rand", Vec::new()))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(first, second);
+        assert!(first
+            .iter()
+            .all(|x| x.as_f64().is_some_and(|x| (0.0..1.0).contains(&x))));
+        Ok(())
+    }
+
+    #[test]
+    fn ranges() -> Result<(), String> {
+        seed(7);
+        for _ in 0..100 {
+            let Type::Int(n) = functions::call("randint", vec![Type::Int(-2), Type::Int(2)])?
+            else {
+                panic!("randint should result in an integer");
+            };
+            assert!((-2..=2).contains(&n));
+        }
+        assert_eq!(
+            functions::call("randint", vec![Type::Int(3), Type::Int(3)])?,
+            Type::Int(3)
+        );
+        assert!(functions::call("randint", vec![Type::Int(i32::MIN), Type::Int(i32::MAX)]).is_ok());
+        assert!(functions::call("randint", vec![Type::Int(3), Type::Int(2)]).is_err());
+
+        let list = Type::List(vec![Type::Int(1), Type::from("a")]);
+        assert!(matches!(
+            functions::call("choice", vec![list])?,
+            Type::Int(1) | Type::String(_)
+        ));
+        assert!(functions::call("choice", vec![Type::List(Vec::new())]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn normal_distribution() -> Result<(), String> {
+        seed(1);
+        let samples: Vec<f64> = (0..10_000)
+            .map(|_| functions::call("normal", vec![Type::Int(10), Type::Float(2.0)]))
+            .map(|x| x.map(|x| x.as_f64().unwrap_or(f64::NAN)))
+            .collect::<Result<_, _>>()?;
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let variance =
+            samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+        assert!((mean - 10.0).abs() < 0.1);
+        assert!((variance - 4.0).abs() < 0.2);
+        assert!(functions::call("normal", vec![Type::Int(0), Type::Int(-1)]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+
+use crate::type_enum::Type;
+
+/// Looks up the values of variables by name.
+///
+/// Implemented for `HashMap<String, Type>`, `BTreeMap<String, Type>` and closures taking a
+/// `&str` and returning an `Option<Type>`.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::{eval, resolver::Resolver, type_enum::Type};
+///
+/// let resolver = |name: &str| name.strip_prefix("x").and_then(|n| n.parse().ok()).map(Type::Int);
+/// assert_eq!(resolver.get("x3"), Some(Type::Int(3)));
+/// assert_eq!(eval("x1 + x2", Some(&resolver)), Ok(Type::Int(3)));
+/// ```
+pub trait Resolver {
+    /// Returns the value of the variable `name`, or `None` if it is not defined.
+    fn get(&self, name: &str) -> Option<Type>;
+}
+
+impl<S: BuildHasher> Resolver for HashMap<String, Type, S> {
+    fn get(&self, name: &str) -> Option<Type> {
+        HashMap::get(self, name).cloned()
+    }
+}
+
+impl Resolver for BTreeMap<String, Type> {
+    fn get(&self, name: &str) -> Option<Type> {
+        BTreeMap::get(self, name).cloned()
+    }
+}
+
+impl<F: Fn(&str) -> Option<Type>> Resolver for F {
+    fn get(&self, name: &str) -> Option<Type> {
+        self(name)
+    }
+}
+
+/// A scope of variables layered over a parent resolver, e.g. the map from `vars_init`.
+///
+/// Bindings in a scope shadow those of its parent, which is only ever read,
+/// so temporary bindings never leak into the caller's variables.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::{eval, vars_init, resolver::Scope, type_enum::Type};
+///
+/// let mut variables = vars_init();
+/// variables.insert("x".to_string(), Type::Int(1));
+/// variables.insert("y".to_string(), Type::Int(2));
+///
+/// let mut scope = Scope::new(Some(&variables));
+/// scope.set("x", Type::Int(10));
+/// let mut inner = scope.child();
+/// inner.set("y", Type::Int(20));
+///
+/// assert_eq!(eval("x + y", Some(&inner)), Ok(Type::Int(30)));
+/// assert_eq!(eval("x + y", Some(&scope)), Ok(Type::Int(12)));
+/// assert_eq!(eval("x + y", Some(&variables)), Ok(Type::Int(3)));
+/// ```
+pub struct Scope<'a> {
+    vars: HashMap<String, Type>,
+    parent: Option<&'a dyn Resolver>,
+}
+
+impl<'a> Scope<'a> {
+    /// Creates an empty scope on top of `parent`, or without a parent if it is `None`.
+    pub fn new(parent: Option<&'a dyn Resolver>) -> Scope<'a> {
+        Scope {
+            vars: HashMap::new(),
+            parent,
+        }
+    }
+
+    /// Creates an empty scope on top of this one.
+    pub fn child(&self) -> Scope<'_> {
+        Scope::new(Some(self))
+    }
+
+    /// Binds the variable `name` to `value` in this scope, shadowing any binding of the parents.
+    pub fn set(&mut self, name: &str, value: Type) {
+        self.vars.insert(name.to_string(), value);
+    }
+
+    /// Returns the variables bound in this scope, without those of the parents.
+    pub fn vars(&self) -> &HashMap<String, Type> {
+        &self.vars
+    }
+}
+
+impl Resolver for Scope<'_> {
+    fn get(&self, name: &str) -> Option<Type> {
+        match self.vars.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.parent?.get(name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_and_closures() {
+        let mut hash_map = HashMap::new();
+        hash_map.insert("a".to_string(), Type::Int(1));
+        let btree_map: BTreeMap<String, Type> = hash_map.clone().into_iter().collect();
+        let closure = |name: &str| (name == "a").then_some(Type::Int(1));
+
+        for resolver in [&hash_map as &dyn Resolver, &btree_map, &closure] {
+            assert_eq!(resolver.get("a"), Some(Type::Int(1)));
+            assert_eq!(resolver.get("b"), None);
+        }
+    }
+
+    #[test]
+    fn scopes_shadow_parent() {
+        let parent = |name: &str| match name {
+            "a" => Some(Type::Int(1)),
+            "b" => Some(Type::Int(2)),
+            _ => None,
+        };
+        let mut scope = Scope::new(Some(&parent));
+        scope.set("b", Type::Int(3));
+        let mut child = scope.child();
+        child.set("c", Type::Int(4));
+        assert_eq!(child.get("a"), Some(Type::Int(1)));
+        assert_eq!(child.get("b"), Some(Type::Int(3)));
+        assert_eq!(child.get("c"), Some(Type::Int(4)));
+        assert_eq!(scope.get("c"), None);
+        assert_eq!(scope.vars().len(), 1);
+        assert_eq!(Scope::new(None).get("a"), None);
+    }
+}
@@ -0,0 +1,488 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use crate::constants;
+use crate::expr::{Expr, Function};
+use crate::interval::Interval;
+use crate::json::Json;
+use crate::measurement::Measurement;
+use crate::polynomial::Polynomial;
+use crate::type_enum::Type;
+use crate::workspace::Workspace;
+
+/// The version of the session files written by `Session::to_json`.
+const VERSION: &str = "1";
+
+/// The state of an interactive session: the variables with their formulas and which of them are read-only.
+///
+/// Sessions can be saved to and loaded from human-readable JSON files, restoring every `Type` exactly,
+/// including the precision of floats and user defined functions.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::{session::Session, type_enum::Type};
+///
+/// let mut session = Session::new();
+/// session.workspace_mut().set("x", Type::Float(0.1)).unwrap();
+/// session.workspace_mut().define("y", "x * 3").unwrap();
+/// session.set_read_only("x");
+///
+/// let restored = Session::from_json(&session.to_json()).unwrap();
+/// assert_eq!(restored.workspace().values()["y"], Type::Float(0.1 * 3.0));
+/// assert!(restored.is_read_only("x"));
+/// assert!(restored.is_read_only("pi"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Session {
+    workspace: Workspace,
+    read_only: BTreeSet<String>,
+}
+
+impl Session {
+    /// Creates an empty session.
+    pub fn new() -> Session {
+        Session::default()
+    }
+
+    /// Returns the variables of the session.
+    pub fn workspace(&self) -> &Workspace {
+        &self.workspace
+    }
+
+    /// Returns the variables of the session for changing them.
+    pub fn workspace_mut(&mut self) -> &mut Workspace {
+        &mut self.workspace
+    }
+
+    /// Returns `true` if the variable `name` is read-only, i.e. a built-in constant or marked by `set_read_only`.
+    pub fn is_read_only(&self, name: &str) -> bool {
+        constants::is_constant(name) || self.read_only.contains(name)
+    }
+
+    /// Marks the variable `name` as read-only.
+    pub fn set_read_only(&mut self, name: &str) {
+        self.read_only.insert(name.to_string());
+    }
+
+    /// Returns `true` if the variable `name` was marked as read-only by `set_read_only`.
+    pub(crate) fn is_marked_read_only(&self, name: &str) -> bool {
+        self.read_only.contains(name)
+    }
+
+    /// Restores the value, formula and read-only flag of the variable `name` exactly, see `Workspace::restore`.
+    pub(crate) fn restore(
+        &mut self,
+        name: &str,
+        value: Option<Type>,
+        formula: Option<Expr>,
+        read_only: bool,
+    ) {
+        self.workspace.restore(name, value, formula);
+        if read_only {
+            self.read_only.insert(name.to_string());
+        } else {
+            self.read_only.remove(name);
+        }
+    }
+
+    /// Stores the result of an evaluation as the next numbered result `$1`, `$2`, …,
+    /// as `_` for the last result and as `res`, returning its number.
+    ///
+    /// The results are stored like all other variables, so they are saved with the session and can be used in equations.
+    /// Recomputing formulas depending on them may fail, in which case the first error is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_little_eval::{eval, session::Session, type_enum::Type};
+    ///
+    /// let mut session = Session::new();
+    /// assert_eq!(session.push_result(Type::Int(6)), Ok(1));
+    /// assert_eq!(session.push_result(Type::Int(7)), Ok(2));
+    /// assert_eq!(eval("$1 * _", Some(session.workspace())), Ok(Type::Int(42)));
+    /// ```
+    pub fn push_result(&mut self, value: Type) -> Result<usize, String> {
+        let number = self.next_result_number();
+        let results = [format!("${}", number), "_".to_string(), "res".to_string()];
+        let mut result = Ok(number);
+        for name in results {
+            if let Err(err) = self.workspace.set(&name, value.clone()) {
+                result = result.and(Err(err));
+            }
+        }
+        result
+    }
+
+    /// Returns the number the next result stored by `Session::push_result` gets.
+    pub fn next_result_number(&self) -> usize {
+        self.results().last().map_or(0, |(number, _)| *number) + 1
+    }
+
+    /// Returns the numbered results stored by `Session::push_result`, sorted by their number.
+    pub fn results(&self) -> Vec<(usize, &Type)> {
+        let mut results: Vec<(usize, &Type)> = self
+            .workspace
+            .values()
+            .iter()
+            .filter_map(|(name, value)| Some((name.strip_prefix('$')?.parse().ok()?, value)))
+            .collect();
+        results.sort_by_key(|(number, _)| *number);
+        results
+    }
+
+    /// Returns the sorted names of the variables whose value, formula or read-only flag differ in `other`.
+    pub fn changed_variables(&self, other: &Session) -> Vec<String> {
+        let names: BTreeSet<&str> = [self, other]
+            .into_iter()
+            .flat_map(|session| {
+                let formulas = session.workspace.formulas().map(|(name, _)| name);
+                let values = session.workspace.values().keys().map(String::as_str);
+                let read_only = session.read_only.iter().map(String::as_str);
+                formulas.chain(values).chain(read_only)
+            })
+            .collect();
+        names
+            .into_iter()
+            .filter(|name| {
+                let (value, other_value) = (
+                    self.workspace.values().get(*name),
+                    other.workspace.values().get(*name),
+                );
+                // Debug compares NaN equal to itself
+                (value != other_value && format!("{:?}", value) != format!("{:?}", other_value))
+                    || self.workspace.formula(name) != other.workspace.formula(name)
+                    || self.read_only.contains(*name) != other.read_only.contains(*name)
+            })
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Writes the session as JSON.
+    ///
+    /// Variables defined by formulas are stored as their formulas, all others as their `Type` and value,
+    /// e.g. `{"type": "f64", "value": 0.1}`.
+    pub fn to_json(&self) -> String {
+        let mut names: Vec<&String> = self
+            .workspace
+            .values()
+            .keys()
+            .filter(|name| self.workspace.formula(name).is_none())
+            .collect();
+        names.sort();
+        let variables = names
+            .into_iter()
+            .map(|name| (name.clone(), type_to_json(&self.workspace.values()[name])))
+            .collect();
+        let formulas = self
+            .workspace
+            .formulas()
+            .map(|(name, formula)| (name.to_string(), Json::String(formula.to_string())))
+            .collect();
+        let read_only = self.read_only.iter().cloned().map(Json::String).collect();
+        let json = Json::Object(vec![
+            ("version".to_string(), Json::Number(VERSION.to_string())),
+            ("variables".to_string(), Json::Object(variables)),
+            ("formulas".to_string(), Json::Object(formulas)),
+            ("read_only".to_string(), Json::Array(read_only)),
+        ]);
+        format!("{}\n", json)
+    }
+
+    /// Parses a session written by `Session::to_json`.
+    pub fn from_json(json: &str) -> Result<Session, String> {
+        let json = Json::parse(json)?;
+        match json.get("version") {
+            Some(Json::Number(version)) if version == VERSION => (),
+            _ => {
+                return Err(format!(
+                    "Only sessions of version {} are supported",
+                    VERSION
+                ))
+            }
+        }
+        let mut session = Session::new();
+        for (name, value) in entries(&json, "variables")? {
+            let value = type_from_json(value).map_err(|err| format!("{}: {}", name, err))?;
+            // there are no formulas yet, which could fail to be recomputed
+            let _ = session.workspace.set(name, value);
+        }
+        for (name, formula) in entries(&json, "formulas")? {
+            let formula = formula
+                .as_str()
+                .ok_or(format!("The formula of {} must be a string", name))?;
+            // formulas depending on formulas restored later fail until those are defined
+            let _ = session.workspace.define(name, formula);
+            if session.workspace.formula(name).is_none() {
+                return Err(format!(
+                    "{}: Unable to restore the formula {}",
+                    name, formula
+                ));
+            }
+        }
+        if let Some(read_only) = json.get("read_only") {
+            for name in read_only.as_array().ok_or("read_only must be a list")? {
+                session.set_read_only(name.as_str().ok_or("read_only must be a list of names")?);
+            }
+        }
+        Ok(session)
+    }
+
+    /// Loads a session from the JSON file at `path`, see `Session::from_json`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Session, String> {
+        let json = fs::read_to_string(&path)
+            .map_err(|err| format!("Unable to read {}: {}", path.as_ref().display(), err))?;
+        Session::from_json(&json)
+    }
+
+    /// Saves the session as JSON to the file at `path`, see `Session::to_json`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        fs::write(&path, self.to_json())
+            .map_err(|err| format!("Unable to write {}: {}", path.as_ref().display(), err))
+    }
+}
+
+/// Returns the entries of the object `key` of `json`, which may be missing.
+fn entries<'a>(json: &'a Json, key: &str) -> Result<&'a [(String, Json)], String> {
+    match json.get(key) {
+        Some(value) => value
+            .as_object()
+            .ok_or(format!("{} must be an object", key)),
+        None => Ok(&[]),
+    }
+}
+
+fn type_to_json(value: &Type) -> Json {
+    let fields = match value {
+        Type::Int(value) => vec![("value", Json::Number(value.to_string()))],
+        Type::Float(value) => vec![("value", Json::float(*value))],
+        Type::String(value) => vec![("value", Json::String(value.clone()))],
+        Type::Interval(value) => vec![
+            ("lo", Json::float(value.lo())),
+            ("hi", Json::float(value.hi())),
+        ],
+        Type::Measurement(value) => vec![
+            ("value", Json::float(value.value())),
+            ("uncertainty", Json::float(value.uncertainty())),
+        ],
+        Type::List(items) => vec![(
+            "items",
+            Json::Array(items.iter().map(type_to_json).collect()),
+        )],
+        Type::Polynomial(value) => vec![(
+            "coefficients",
+            Json::Array(value.coefficients().into_iter().map(Json::float).collect()),
+        )],
+        Type::Function(value) => vec![
+            (
+                "params",
+                Json::Array(value.params().iter().cloned().map(Json::String).collect()),
+            ),
+            ("body", Json::String(value.body().to_string())),
+        ],
+    };
+    let mut object = vec![(
+        "type".to_string(),
+        Json::String(value.get_type().to_string()),
+    )];
+    object.extend(
+        fields
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value)),
+    );
+    Json::Object(object)
+}
+
+fn type_from_json(json: &Json) -> Result<Type, String> {
+    let field = |key: &str| json.get(key).ok_or(format!("The value is missing {}", key));
+    let float = |key: &str| {
+        field(key)?
+            .as_f64()
+            .ok_or(format!("{} must be a number", key))
+    };
+    let list = |key: &str| {
+        field(key)?
+            .as_array()
+            .ok_or(format!("{} must be a list", key))
+    };
+    let kind = field("type")?.as_str().ok_or("type must be a string")?;
+    match kind {
+        "i32" => match field("value")? {
+            Json::Number(value) => value
+                .parse()
+                .map(Type::Int)
+                .map_err(|_| format!("{} is not an i32", value)),
+            _ => Err("value must be a number".to_string()),
+        },
+        "f64" => Ok(Type::Float(float("value")?)),
+        "String" => Ok(Type::String(
+            field("value")?
+                .as_str()
+                .ok_or("value must be a string")?
+                .to_string(),
+        )),
+        "Interval" => Ok(Type::Interval(Interval::new(float("lo")?, float("hi")?)?)),
+        "Measurement" => Ok(Type::Measurement(Measurement::new(
+            float("value")?,
+            float("uncertainty")?,
+        )?)),
+        "List" => Ok(Type::List(
+            list("items")?
+                .iter()
+                .map(type_from_json)
+                .collect::<Result<Vec<Type>, String>>()?,
+        )),
+        "Polynomial" => Ok(Type::Polynomial(Polynomial::new(
+            list("coefficients")?
+                .iter()
+                .map(|c| c.as_f64().ok_or("coefficients must be numbers".to_string()))
+                .collect::<Result<Vec<f64>, String>>()?,
+        ))),
+        "Function" => {
+            let params = list("params")?
+                .iter()
+                .map(|param| {
+                    param
+                        .as_str()
+                        .map(str::to_string)
+                        .ok_or("params must be names".to_string())
+                })
+                .collect::<Result<Vec<String>, String>>()?;
+            let body = field("body")?.as_str().ok_or("body must be a string")?;
+            Ok(Type::Function(Function::new(params, Expr::parse(body)?)))
+        }
+        kind => Err(format!("Unknown type {}", kind)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restores_every_type() -> Result<(), String> {
+        let values = [
+            Type::Int(-7),
+            Type::Float(0.1 + 0.2),
+            Type::Float(f64::INFINITY),
+            Type::String("say \"hi\"".to_string()),
+            Type::Interval(Interval::new(1.9, 2.1)?),
+            Type::Measurement(Measurement::new(9.81, 0.02)?),
+            Type::List(vec![Type::Int(1), Type::List(Vec::new())]),
+            Type::Polynomial(Polynomial::new(vec![1.0, -3.0, 2.5])),
+            Type::Function(Function::new(
+                vec!["x".to_string()],
+                Expr::parse("x^2 + 0.1")?,
+            )),
+        ];
+        let mut session = Session::new();
+        for (idx, value) in values.iter().enumerate() {
+            session
+                .workspace_mut()
+                .set(&format!("v{}", idx), value.clone())?;
+        }
+        let restored = Session::from_json(&session.to_json())?;
+        for (idx, value) in values.iter().enumerate() {
+            assert_eq!(&restored.workspace().values()[&format!("v{}", idx)], value);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn restores_string_literals() -> Result<(), String> {
+        let mut session = Session::new();
+        let body = Expr::parse("\"1\" + x")?;
+        session.workspace_mut().set(
+            "f",
+            Type::Function(Function::new(vec!["x".to_string()], body)),
+        )?;
+        session.workspace_mut().set("NAME", Type::Int(3))?;
+        session
+            .workspace_mut()
+            .define("g", "env(\"MY_LITTLE_EVAL_UNSET\", \"NAME\")")?;
+
+        let restored = Session::from_json(&session.to_json())?;
+        for session in [&session, &restored] {
+            assert_eq!(
+                crate::eval("f(2)", Some(session.workspace())),
+                Ok(Type::String("12".to_string()))
+            );
+            assert_eq!(
+                session.workspace().values()["g"],
+                Type::String("NAME".to_string())
+            );
+        }
+        assert_eq!(
+            restored.workspace().formula("g"),
+            session.workspace().formula("g")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn restores_formulas_in_any_order() -> Result<(), String> {
+        let json = r#"{
+            "version": 1,
+            "variables": {"a": {"type": "i32", "value": 2}},
+            "formulas": {"b": "c + 1", "c": "a * 10"},
+            "read_only": ["a"]
+        }"#;
+        let session = Session::from_json(json)?;
+        assert_eq!(session.workspace().values()["b"], Type::Int(21));
+        assert!(session.is_read_only("a"));
+        assert!(!session.is_read_only("b"));
+        Ok(())
+    }
+
+    #[test]
+    fn numbered_results() -> Result<(), String> {
+        let mut session = Session::new();
+        session.workspace_mut().define("double", "_ * 2")?;
+        session.push_result(Type::Int(3))?;
+        session.push_result(Type::Float(0.5))?;
+        assert_eq!(session.workspace().values()["double"], Type::Float(1.0));
+
+        let mut restored = Session::from_json(&session.to_json())?;
+        assert_eq!(restored.push_result(Type::Int(9)), Ok(3));
+        assert_eq!(
+            restored.results(),
+            [
+                (1, &Type::Int(3)),
+                (2, &Type::Float(0.5)),
+                (3, &Type::Int(9))
+            ]
+        );
+        assert_eq!(restored.workspace().values()["res"], Type::Int(9));
+        Ok(())
+    }
+
+    #[test]
+    fn changed_variables() -> Result<(), String> {
+        let mut before = Session::new();
+        before.workspace_mut().set("a", Type::Int(1))?;
+        before.workspace_mut().set("b", Type::Float(f64::NAN))?;
+        before.workspace_mut().define("c", "a + 1")?;
+        let mut after = before.clone();
+        assert!(after.changed_variables(&before).is_empty());
+
+        after.workspace_mut().set("a", Type::Int(2))?;
+        after.set_read_only("d");
+        assert_eq!(after.changed_variables(&before), ["a", "c", "d"]);
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        assert!(Session::from_json("{}").is_err());
+        assert!(Session::from_json(
+            r#"{"version": 1, "variables": {"a": {"type": "i32", "value": 2.5}}}"#
+        )
+        .is_err());
+        assert!(
+            Session::from_json(r#"{"version": 1, "variables": {"a": {"type": "Complex"}}}"#)
+                .is_err()
+        );
+        assert!(Session::from_json(r#"{"version": 1, "formulas": {"a": "a + 1"}}"#).is_err());
+    }
+}
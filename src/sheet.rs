@@ -0,0 +1,451 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::expr::Expr;
+use crate::resolver::Resolver;
+use crate::type_enum::Type;
+use crate::workspace::Workspace;
+
+/// The maximum number of cells a range like `A1:B10` may contain.
+const MAX_RANGE_CELLS: usize = 10_000;
+
+/// A spreadsheet of cells addressed like `A1` or `B2`, holding values or formulas starting with `=`.
+///
+/// Formulas can reference other cells and ranges like `A1:A10`, which evaluate to the list of their cells
+/// in row-major order, e.g. for the spreadsheet functions `SUM`, `AVG`, `MIN`, `MAX` and `COUNT`.
+/// Whenever a cell changes, the formulas depending on it are recalculated.
+/// Cells without a value, e.g. empty cells, are ignored by the spreadsheet functions when they are part of a range,
+/// and are 0 when they are referenced on their own, e.g. `=A1 + 1` is 1 while `A1` is empty.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::{sheet::Sheet, type_enum::Type};
+///
+/// let mut sheet = Sheet::from_csv("price,qty,total\n3,2,=A2*B2\n4,5,=A3*B3\n,,=SUM(C2:C3)\n").unwrap();
+/// assert_eq!(sheet.value("C4"), Some(&Type::Int(26)));
+///
+/// sheet.set("B2", "10").unwrap();
+/// assert_eq!(sheet.value("C4"), Some(&Type::Int(50)));
+/// assert_eq!(sheet.to_csv(), "price,qty,total\n3,10,=A2*B2\n4,5,=A3*B3\n,,=SUM(C2:C3)\n");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Sheet {
+    inputs: BTreeMap<(usize, usize), String>,
+    workspace: Workspace,
+}
+
+impl Sheet {
+    /// Creates an empty sheet.
+    pub fn new() -> Sheet {
+        Sheet::default()
+    }
+
+    /// Sets the cell `cell` to `input` and recalculates the formulas depending on it.
+    ///
+    /// Inputs starting with `=` are formulas, other inputs are values like `3.5` or text,
+    /// and an empty input clears the cell.
+    ///
+    /// Returns an error without changing the cell if the formula can't be parsed or would create a cyclic dependency.
+    /// If only calculating a formula fails, the cell is set but has no value until its dependencies change.
+    pub fn set(&mut self, cell: &str, input: &str) -> Result<(), String> {
+        let position = parse_cell(cell).ok_or(format!("{} is not a valid cell", cell))?;
+        let input = input.trim();
+        if input.is_empty() {
+            self.inputs.remove(&position);
+            return self.workspace.remove(cell).map(|_| ());
+        }
+        let result = match input.strip_prefix('=') {
+            Some(formula) => {
+                let formula = expand_references(formula)?;
+                let expr = Expr::parse(&formula)?;
+                let result = self.workspace.define(cell, &formula);
+                // a rejected formula leaves the cell unchanged
+                if self.workspace.formula(cell) != Some(&expr) {
+                    return result;
+                }
+                result
+            }
+            None => self.workspace.set(cell, Type::from(input)),
+        };
+        self.inputs.insert(position, input.to_string());
+        result
+    }
+
+    /// Returns the current value of the cell `cell`, or `None` if it is empty or its formula failed.
+    pub fn value(&self, cell: &str) -> Option<&Type> {
+        self.workspace.values().get(cell)
+    }
+
+    /// Returns what was entered into the cell `cell`, e.g. a formula like `=SUM(A1:A3)`.
+    pub fn input(&self, cell: &str) -> Option<&str> {
+        self.inputs.get(&parse_cell(cell)?).map(String::as_str)
+    }
+
+    /// Returns the names and inputs of all cells that are not empty, row by row.
+    pub fn cells(&self) -> impl Iterator<Item = (String, &str)> {
+        self.inputs
+            .iter()
+            .map(|((row, col), input)| (cell_name(*col, *row), input.as_str()))
+    }
+
+    /// Parses a sheet from CSV, where the first line is row 1 and the first column is column `A`.
+    ///
+    /// Fields containing commas, quotes or line breaks must be quoted with `"`, doubling the quotes inside.
+    pub fn from_csv(csv: &str) -> Result<Sheet, String> {
+        let mut sheet = Sheet::new();
+        for (row, fields) in parse_csv(csv)?.into_iter().enumerate() {
+            for (col, field) in fields.iter().enumerate() {
+                if field.trim().is_empty() {
+                    continue;
+                }
+                let cell = cell_name(col, row + 1);
+                // formulas referencing cells further down fail until those are set, only rejected formulas are errors
+                if let Err(error) = sheet.set(&cell, field) {
+                    if sheet.input(&cell).is_none() {
+                        return Err(format!("{}: {}", cell, error));
+                    }
+                }
+            }
+        }
+        Ok(sheet)
+    }
+
+    /// Writes the inputs of the sheet as CSV, keeping the formulas.
+    pub fn to_csv(&self) -> String {
+        let rows = self.inputs.keys().map(|(row, _)| *row).max().unwrap_or(0);
+        let cols = self
+            .inputs
+            .keys()
+            .map(|(_, col)| col + 1)
+            .max()
+            .unwrap_or(0);
+        let mut csv = String::new();
+        for row in 1..=rows {
+            let fields: Vec<String> = (0..cols)
+                .map(|col| {
+                    self.inputs
+                        .get(&(row, col))
+                        .map_or(String::new(), |input| quote(input))
+                })
+                .collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Loads a sheet from the CSV file at `path`, see `Sheet::from_csv`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Sheet, String> {
+        let csv = fs::read_to_string(&path)
+            .map_err(|err| format!("Unable to read {}: {}", path.as_ref().display(), err))?;
+        Sheet::from_csv(&csv)
+    }
+
+    /// Saves the sheet as CSV to the file at `path`, see `Sheet::to_csv`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        fs::write(&path, self.to_csv())
+            .map_err(|err| format!("Unable to write {}: {}", path.as_ref().display(), err))
+    }
+}
+
+impl Resolver for Sheet {
+    fn get(&self, name: &str) -> Option<Type> {
+        self.workspace.get(name)
+    }
+}
+
+/// Calls the spreadsheet functions, which ignore everything but numbers, e.g. empty cells and text.
+pub(crate) fn call(name: &str, args: &[Type]) -> Option<Result<Type, String>> {
+    if !matches!(name, "SUM" | "AVG" | "AVERAGE" | "MIN" | "MAX" | "COUNT") {
+        return None;
+    }
+    let mut numbers = Vec::new();
+    collect_numbers(args, &mut numbers);
+    let by_value = |a: &&Type, b: &&Type| {
+        a.as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    };
+    Some(match name {
+        "SUM" => numbers
+            .into_iter()
+            .cloned()
+            .try_fold(Type::Int(0), |sum, x| sum + x),
+        "AVG" | "AVERAGE" => match numbers.len() {
+            0 => Err(format!("Unable to calculate the {} of no numbers", name)),
+            len => Ok(Type::Float(
+                numbers.iter().filter_map(|x| x.as_f64()).sum::<f64>() / len as f64,
+            )),
+        },
+        "MIN" => Ok(numbers
+            .into_iter()
+            .min_by(by_value)
+            .cloned()
+            .unwrap_or(Type::Int(0))),
+        "MAX" => Ok(numbers
+            .into_iter()
+            .max_by(by_value)
+            .cloned()
+            .unwrap_or(Type::Int(0))),
+        _ => Ok(Type::Int(numbers.len() as i32)),
+    })
+}
+
+fn collect_numbers<'a>(args: &'a [Type], numbers: &mut Vec<&'a Type>) {
+    for arg in args {
+        match arg {
+            Type::Int(_) | Type::Float(_) => numbers.push(arg),
+            Type::List(items) => collect_numbers(items, numbers),
+            _ => (),
+        }
+    }
+}
+
+/// Parses a cell like `B12` into its row, starting at 1, and its column, starting at 0.
+fn parse_cell(cell: &str) -> Option<(usize, usize)> {
+    let digits = cell.find(|c: char| c.is_ascii_digit())?;
+    let (letters, row) = cell.split_at(digits);
+    if letters.is_empty() || letters.len() > 3 || !letters.chars().all(|c| c.is_ascii_uppercase()) {
+        return None;
+    }
+    if row.starts_with('0') || !row.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let col = letters
+        .bytes()
+        .fold(0, |col, letter| col * 26 + (letter - b'A') as usize + 1);
+    Some((row.parse().ok()?, col - 1))
+}
+
+/// Returns the name of the cell in column `col`, starting at 0, and row `row`, starting at 1.
+fn cell_name(col: usize, row: usize) -> String {
+    let mut letters = Vec::new();
+    let mut col = col + 1;
+    while col > 0 {
+        letters.push(b'A' + ((col - 1) % 26) as u8);
+        col = (col - 1) / 26;
+    }
+    letters.reverse();
+    format!("{}{}", String::from_utf8_lossy(&letters), row)
+}
+
+/// Prepares a formula for its evaluation by replacing the ranges like `A1:B2` by the lists of their cells,
+/// e.g. `[A1, B1, A2, B2]`, and single cells like `A1` by `CELL(A1)`, which is 0 while the cell has no value.
+/// String literals are kept as they are.
+fn expand_references(formula: &str) -> Result<String, String> {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let word_len = |s: &str| s.find(|c: char| !is_word(c)).unwrap_or(s.len());
+    let mut expanded = String::new();
+    let mut rest = formula;
+    while let Some(c) = rest.chars().next() {
+        if c == '"' {
+            let len = literal_len(rest);
+            expanded.push_str(&rest[..len]);
+            rest = &rest[len..];
+            continue;
+        }
+        let len = word_len(rest);
+        if len == 0 {
+            expanded.push(c);
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        let (word, after) = rest.split_at(len);
+        if let Some(after) = after.strip_prefix(':') {
+            let (to, after) = after.split_at(word_len(after));
+            expanded.push_str(&expand_range(word, to)?);
+            rest = after;
+        } else {
+            // cells without a following `(`, which would be a function like `LOG10(x)`
+            if parse_cell(word).is_some() && !after.trim_start().starts_with('(') {
+                expanded.push_str(&format!("CELL({})", word));
+            } else {
+                expanded.push_str(word);
+            }
+            rest = after;
+        }
+    }
+    Ok(expanded)
+}
+
+/// Returns the length of the string literal at the start of `s` including its quotes,
+/// where `\"` and `\\` escape a quote and a backslash like in equations.
+fn literal_len(s: &str) -> usize {
+    let mut escaped = false;
+    for (idx, c) in s.char_indices().skip(1) {
+        match c {
+            '"' if !escaped => return idx + 1,
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+    s.len()
+}
+
+/// Returns the list of the cells of the range `from:to`, e.g. `[A1, B1, A2, B2]` for `A1:B2`.
+fn expand_range(from: &str, to: &str) -> Result<String, String> {
+    let (Some((row0, col0)), Some((row1, col1))) = (parse_cell(from), parse_cell(to)) else {
+        return Err(format!("{}:{} is not a valid range", from, to));
+    };
+    let (rows, cols) = (
+        row0.min(row1)..=row0.max(row1),
+        col0.min(col1)..=col0.max(col1),
+    );
+    if rows.clone().count() * cols.clone().count() > MAX_RANGE_CELLS {
+        return Err(format!(
+            "Ranges may contain at most {} cells, {}:{} is too large",
+            MAX_RANGE_CELLS, from, to
+        ));
+    }
+    let cells: Vec<String> = rows
+        .flat_map(|row| cols.clone().map(move |col| cell_name(col, row)))
+        .collect();
+    Ok(format!("[{}]", cells.join(", ")))
+}
+
+/// Splits CSV into rows of fields.
+fn parse_csv(csv: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.is_empty() => in_quotes = true,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes && chars.peek() == Some(&'\n') => (),
+            '\n' if !in_quotes => {
+                fields.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut fields));
+            }
+            c => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("All quotes must be closed!".to_string());
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        rows.push(fields);
+    }
+    Ok(rows)
+}
+
+/// Quotes a CSV field if necessary.
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_and_ranges() -> Result<(), String> {
+        assert_eq!(parse_cell("A1"), Some((1, 0)));
+        assert_eq!(parse_cell("AB12"), Some((12, 27)));
+        assert_eq!(parse_cell("a1"), None);
+        assert_eq!(parse_cell("A0"), None);
+        assert_eq!(cell_name(27, 12), "AB12");
+        assert_eq!(cell_name(25, 1), "Z1");
+        assert_eq!(
+            expand_references("SUM(A1:B2) + C1")?,
+            "SUM([A1, B1, A2, B2]) + CELL(C1)"
+        );
+        assert_eq!(expand_references("A3:A1")?, "[A1, A2, A3]");
+        assert_eq!(
+            expand_references("\"12:30 \\\" A1\" + LOG10(x1)")?,
+            "\"12:30 \\\" A1\" + LOG10(x1)"
+        );
+        assert!(expand_references("x:A1").is_err());
+        assert!(expand_references("A1:ZZ9999").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn functions_and_recalculation() -> Result<(), String> {
+        let mut sheet = Sheet::new();
+        sheet.set("A1", "1")?;
+        sheet.set("A2", "2.5")?;
+        sheet.set("A3", "text")?;
+        sheet.set("B1", "=SUM(A1:A4)")?;
+        sheet.set("B2", "=AVG(A1:A4) + COUNT(A1:A4)")?;
+        sheet.set("B3", "=MAX(A1:A4, 7) - MIN(A1:A4)")?;
+        assert_eq!(sheet.value("B1"), Some(&Type::Float(3.5)));
+        assert_eq!(sheet.value("B2"), Some(&Type::Float(3.75)));
+        assert_eq!(sheet.value("B3"), Some(&Type::Int(6)));
+
+        sheet.set("A4", "=A1 * 10 - 1")?;
+        assert_eq!(sheet.value("B1"), Some(&Type::Float(12.5)));
+        sheet.set("A1", "")?;
+        assert_eq!(sheet.input("A1"), None);
+        assert_eq!(sheet.value("A4"), Some(&Type::Int(-1)));
+        assert_eq!(sheet.value("B1"), Some(&Type::Float(1.5)));
+        assert!(sheet.set("A1", "=B1").is_err());
+        assert_eq!(sheet.input("A1"), None);
+        assert!(sheet.set("1A", "1").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn empty_cells_and_text() -> Result<(), String> {
+        let mut sheet = Sheet::new();
+        sheet.set("B1", "=A1 + 1")?;
+        assert_eq!(sheet.value("B1"), Some(&Type::Int(1)));
+        sheet.set("A1", "2")?;
+        assert_eq!(sheet.value("B1"), Some(&Type::Int(3)));
+        sheet.set("A2", "note")?;
+        sheet.set("B2", "=A2")?;
+        assert_eq!(sheet.value("B2"), Some(&Type::from("note")));
+        sheet.set("B3", "=AVG(A1:A3) + COUNT(A1:A3)")?;
+        assert_eq!(sheet.value("B3"), Some(&Type::Float(3.0)));
+        sheet.set("B4", "=\"12:30\"")?;
+        assert_eq!(sheet.value("B4"), Some(&Type::String("12:30".to_string())));
+        Ok(())
+    }
+
+    #[test]
+    fn running_totals() -> Result<(), String> {
+        // every cell depends on all cells above it, which share their dependencies
+        let mut sheet = Sheet::new();
+        sheet.set("B1", "1")?;
+        for row in 2..=60 {
+            sheet.set(&format!("B{}", row), &format!("=SUM(B1:B{})", row - 1))?;
+        }
+        assert_eq!(sheet.value("B10"), Some(&Type::Int(256)));
+        assert_eq!(
+            sheet.value("B60").and_then(Type::as_f64),
+            Some(2f64.powi(58))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn csv_round_trip() -> Result<(), String> {
+        let csv = "name,value\n\"Smith, John\",\"=SUM(B3:B4, 1)\"\n\"say \"\"hi\"\"\",2\n,3\n";
+        let sheet = Sheet::from_csv(csv)?;
+        assert_eq!(sheet.input("A2"), Some("Smith, John"));
+        assert_eq!(sheet.input("A3"), Some("say \"hi\""));
+        assert_eq!(sheet.value("B2"), Some(&Type::Int(6)));
+        assert_eq!(sheet.to_csv(), csv);
+        assert_eq!(sheet.cells().count(), 7);
+        assert!(Sheet::from_csv("\"open").is_err());
+        assert_eq!(
+            Sheet::from_csv("=A1").map(|_| ()),
+            Err("A1: Cyclic dependency: A1 -> A1".to_string())
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,360 @@
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+use crate::combinatorics;
+use crate::expr::{binary, Expr, Op};
+use crate::functions::MathFn;
+use crate::random;
+use crate::type_enum::Type;
+
+/// A product `coefficient * factor_1^power_1 * ... * factor_n^power_n`.
+#[derive(Debug, Clone)]
+struct Term {
+    coefficient: Type,
+    factors: Vec<(Expr, i32)>,
+}
+
+impl Term {
+    fn constant(coefficient: Type) -> Term {
+        Term {
+            coefficient,
+            factors: Vec::new(),
+        }
+    }
+
+    fn atom(expr: Expr) -> Term {
+        Term {
+            coefficient: Type::Int(1),
+            factors: vec![(expr, 1)],
+        }
+    }
+
+    fn negate(self) -> Term {
+        self.scale(Type::Int(-1))
+    }
+
+    fn scale(self, factor: Type) -> Term {
+        Term {
+            coefficient: fold(Op::Mul, self.coefficient, factor),
+            factors: self.factors,
+        }
+    }
+
+    /// Multiplies two terms, merging the powers of equal factors unless the sum of the powers overflows.
+    fn multiply(self, other: Term) -> Term {
+        let mut factors = self.factors;
+        for (factor, power) in other.factors {
+            let merged = factors
+                .iter_mut()
+                .find(|(f, _)| *f == factor && !is_volatile(f))
+                .and_then(|(_, p)| p.checked_add(power).map(|sum| *p = sum));
+            if merged.is_none() {
+                factors.push((factor, power));
+            }
+        }
+        factors.retain(|(_, power)| *power != 0);
+        factors.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        Term {
+            coefficient: fold(Op::Mul, self.coefficient, other.coefficient),
+            factors,
+        }
+    }
+
+    /// Raises the term to an integer power, returning `None` if the coefficient can't be raised
+    /// or a power overflows.
+    fn powi(self, n: i32) -> Option<Term> {
+        let coefficient = self.coefficient.pow(Type::Int(n)).ok()?;
+        Some(Term {
+            coefficient,
+            factors: self
+                .factors
+                .into_iter()
+                .map(|(factor, power)| Some((factor, power.checked_mul(n)?)))
+                .collect::<Option<_>>()?,
+        })
+    }
+
+    fn to_expr(&self) -> Expr {
+        let mut factors = self.factors.iter().map(|(factor, power)| match power {
+            1 => factor.clone(),
+            _ => Expr::BinOp(
+                Op::Pow,
+                Box::new(factor.clone()),
+                Box::new(Expr::Value(Type::Int(*power))),
+            ),
+        });
+        let Some(first) = factors.next() else {
+            return Expr::Value(self.coefficient.clone());
+        };
+        let mul = |a: Expr, b: Expr| Expr::BinOp(Op::Mul, Box::new(a), Box::new(b));
+        if is_number(&self.coefficient, 1.0) {
+            factors.fold(first, mul)
+        } else if is_number(&self.coefficient, -1.0) {
+            Expr::Neg(Box::new(factors.fold(first, mul)))
+        } else {
+            let coefficient = Expr::Value(self.coefficient.clone());
+            factors.fold(mul(coefficient, first), mul)
+        }
+    }
+}
+
+impl Expr {
+    /// Returns a simplified expression that evaluates to the same value.
+    ///
+    /// Sums and products are flattened and like terms are combined, e.g. `x*1 + 0 + 2*x` becomes `3*x`
+    /// and `x - x` becomes `0`. Subexpressions without variables are folded into their value.
+    /// Calls of random functions and `env` are never combined, as they may result in a different value each time.
+    /// Terms, including the folded constant, keep the order of their first occurrence,
+    /// so a string is still concatenated on the same side of the other operands, e.g. `2 + s` stays `2 + s`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_little_eval::expr::Expr;
+    ///
+    /// let expr = Expr::parse("a - (b - 2 * a) + x * x * 3").unwrap();
+    ///
+    /// assert_eq!(expr.simplify().to_string(), "3*a - b + 3*x^2");
+    /// ```
+    pub fn simplify(&self) -> Expr {
+        sum_to_expr(&sum(self))
+    }
+}
+
+/// Flattens an expression into a sum of terms, combining like terms.
+fn sum(expr: &Expr) -> Vec<Term> {
+    let mut terms: Vec<Term> = Vec::new();
+    let mut add_term = |term: Term| match terms.iter_mut().find(|t| {
+        t.factors == term.factors && !t.factors.iter().any(|(factor, _)| is_volatile(factor))
+    }) {
+        Some(t) => t.coefficient = fold(Op::Add, t.coefficient.clone(), term.coefficient),
+        None => terms.push(term),
+    };
+    match expr {
+        Expr::BinOp(Op::Add, a, b) => {
+            sum(a).into_iter().for_each(&mut add_term);
+            sum(b).into_iter().for_each(&mut add_term);
+        }
+        Expr::BinOp(Op::Sub, a, b) => {
+            sum(a).into_iter().for_each(&mut add_term);
+            sum(b).into_iter().map(Term::negate).for_each(&mut add_term);
+        }
+        Expr::Neg(a) => sum(a).into_iter().map(Term::negate).for_each(&mut add_term),
+        _ => add_term(product(expr)),
+    }
+    terms.retain(|term| !is_number(&term.coefficient, 0.0));
+    terms
+}
+
+/// Flattens an expression into a single term, keeping sums of several terms as a factor.
+fn product(expr: &Expr) -> Term {
+    match expr {
+        Expr::Value(value @ (Type::Int(_) | Type::Float(_))) => Term::constant(value.clone()),
+        Expr::BinOp(Op::Mul, a, b) => product(a).multiply(product(b)),
+        Expr::Neg(_) | Expr::BinOp(Op::Add | Op::Sub, _, _) => {
+            let mut terms = sum(expr);
+            match terms.len() {
+                0 => Term::constant(Type::Int(0)),
+                1 => terms.remove(0),
+                _ => Term::atom(sum_to_expr(&terms)),
+            }
+        }
+        Expr::BinOp(Op::Pow, base, exponent) => {
+            let exponent = exponent.simplify();
+            match exponent {
+                Expr::Value(Type::Int(n)) => {
+                    let base = product(base);
+                    base.clone()
+                        .powi(n)
+                        .unwrap_or_else(|| Term::atom(pow(base.to_expr(), exponent)))
+                }
+                _ => Term::atom(pow(base.simplify(), exponent)),
+            }
+        }
+        _ => match atom(expr) {
+            Expr::Value(value @ (Type::Int(_) | Type::Float(_))) => Term::constant(value),
+            atom => Term::atom(atom),
+        },
+    }
+}
+
+/// Simplifies the operands of an expression that is neither a sum nor a product.
+fn atom(expr: &Expr) -> Expr {
+    match expr {
+        Expr::BinOp(op, a, b) => {
+            let (a, b) = (a.simplify(), b.simplify());
+            if *op == Op::Div && is_value(&b, 1.0) {
+                return a;
+            }
+            binary(*op, a, b)
+        }
+        Expr::Interval(lo, hi) => Expr::Interval(Box::new(lo.simplify()), Box::new(hi.simplify())),
+        Expr::List(items) => Expr::List(items.iter().map(Expr::simplify).collect()),
+        Expr::Let(name, value, body) => Expr::Let(
+            name.clone(),
+            Box::new(value.simplify()),
+            Box::new(body.simplify()),
+        ),
+        Expr::Factorial(a) => match a.simplify() {
+            Expr::Value(value) => match combinatorics::factorial(value.clone()) {
+                Ok(value) => Expr::Value(value),
+                Err(_) => Expr::Factorial(Box::new(Expr::Value(value))),
+            },
+            a => Expr::Factorial(Box::new(a)),
+        },
+        Expr::Call(name, args) => {
+            let args: Vec<Expr> = args.iter().map(Expr::simplify).collect();
+            let is_constant = args
+                .iter()
+                .all(|arg| matches!(arg, Expr::Value(value) if value.as_f64().is_some()));
+            let call = Expr::Call(name.clone(), args);
+            if MathFn::from_name(name).is_some() && is_constant {
+                if let Ok(value) = call.eval(None) {
+                    return Expr::Value(value);
+                }
+            }
+            call
+        }
+        expr => expr.clone(),
+    }
+}
+
+/// Builds the sum of `terms` in their order, which is kept in case the operands are strings.
+fn sum_to_expr(terms: &[Term]) -> Expr {
+    let mut terms = terms.iter();
+    let Some(first) = terms.next() else {
+        return Expr::Value(Type::Int(0));
+    };
+    terms.fold(first.to_expr(), |sum, term| {
+        let negated = term.clone().negate();
+        if term.coefficient.as_f64().is_some_and(|c| c < 0.0) {
+            Expr::BinOp(Op::Sub, Box::new(sum), Box::new(negated.to_expr()))
+        } else {
+            Expr::BinOp(Op::Add, Box::new(sum), Box::new(term.to_expr()))
+        }
+    })
+}
+
+fn pow(base: Expr, exponent: Expr) -> Expr {
+    if is_value(&exponent, 0.0) {
+        Expr::Value(Type::Int(1))
+    } else if is_value(&exponent, 1.0) {
+        base
+    } else {
+        binary(Op::Pow, base, exponent)
+    }
+}
+
+fn fold(op: Op, a: Type, b: Type) -> Type {
+    op.apply(a, b).unwrap_or(Type::Float(f64::NAN))
+}
+
+/// Returns `true` if `expr` calls a function that may result in a different value each time,
+/// like `rand()` or `env("NAME")`, so equal expressions must not be combined.
+fn is_volatile(expr: &Expr) -> bool {
+    let mut calls = BTreeSet::new();
+    expr.collect_calls(&mut calls);
+    calls
+        .iter()
+        .any(|name| random::is_random(name) || name == "env")
+}
+
+fn is_number(value: &Type, number: f64) -> bool {
+    value.as_f64() == Some(number)
+}
+
+fn is_value(expr: &Expr, number: f64) -> bool {
+    matches!(expr, Expr::Value(value) if is_number(value, number))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vars_init;
+
+    fn assert_simplified(equation: &str, simplified: &str) -> Result<(), String> {
+        let expr = Expr::parse(equation)?;
+        let result = expr.simplify();
+        assert_eq!(result.to_string(), simplified, "\n simplify {}", equation);
+
+        let mut vars = vars_init();
+        vars.insert("x".to_string(), Type::Int(3));
+        vars.insert("y".to_string(), Type::Float(-1.5));
+        vars.insert("z".to_string(), Type::Int(7));
+        let (a, b) = (result.eval(Some(&vars))?, expr.eval(Some(&vars))?);
+        assert_eq!(a.as_f64(), b.as_f64(), "\n {} != {}", result, expr);
+        Ok(())
+    }
+
+    #[test]
+    fn like_terms() -> Result<(), String> {
+        assert_simplified("x*1 + 0 + 2*x", "3*x")?;
+        assert_simplified("x - x", "0")?;
+        assert_simplified("y*x - x*y + z", "z")?;
+        assert_simplified("x*x*3 - x^2", "2*x^2")?;
+        Ok(())
+    }
+
+    #[test]
+    fn flattening() -> Result<(), String> {
+        assert_simplified("((x + y) + z)", "x + y + z")?;
+        assert_simplified("x - (y - z)", "x - y + z")?;
+        assert_simplified("-(x - 2*x) * (y * (z * 2))", "2*x*y*z")?;
+        assert_simplified("(2*x)^2 / 1", "4*x^2")?;
+        Ok(())
+    }
+
+    #[test]
+    fn constant_folding() -> Result<(), String> {
+        assert_simplified("2 + 3 * 4 - x", "14 - x")?;
+        assert_simplified("sqrt(16) + z * 0", "4.0")?;
+        assert_simplified("z / 2 + z / 2", "2*(z/2)")?;
+        assert_simplified("(x + 1)*(x*1 + 1)", "(x + 1)^2")?;
+        assert_simplified("3! * x + (z - z + x)!", "6*x + x!")?;
+        Ok(())
+    }
+
+    #[test]
+    fn overflowing_powers() -> Result<(), String> {
+        let simplified =
+            |equation: &str| Expr::parse(equation).map(|expr| expr.simplify().to_string());
+        assert_eq!(simplified("x^2147483647 * x")?, "x^2147483647*x");
+        assert_eq!(simplified("(x^65536)^65536")?, "(x^65536)^65536");
+        assert_eq!(simplified("x^2147483647 * x / x")?, "x^2147483647*x/x");
+        Ok(())
+    }
+
+    #[test]
+    fn string_order() -> Result<(), String> {
+        let vars = std::collections::HashMap::from([("s".to_string(), Type::from("hi"))]);
+        for (equation, simplified, value) in [
+            ("2 + s", "2 + s", "2hi"),
+            ("s + 2", "s + 2", "hi2"),
+            ("1 + 1 + s + x", "2 + s + x", "2hix"),
+        ] {
+            let expr = Expr::parse(equation)?.simplify();
+            assert_eq!(expr.to_string(), simplified);
+            assert_eq!(expr.eval(Some(&vars))?, Type::String(value.to_string()));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn volatile_calls() -> Result<(), String> {
+        let simplified =
+            |equation: &str| Expr::parse(equation).map(|expr| expr.simplify().to_string());
+        assert_eq!(simplified("rand() - rand()")?, "rand() - rand()");
+        assert_eq!(simplified("rand() + rand()")?, "rand() + rand()");
+        assert_eq!(simplified("rand() * rand()")?, "rand()*rand()");
+        assert_eq!(
+            simplified("2*normal(0, 1) - (normal(0, 1) + x)")?,
+            "2*normal(0, 1) - normal(0, 1) - x"
+        );
+        assert_eq!(
+            simplified("env(\"X\", 1) - env(\"X\", 1)")?,
+            "env(\"X\", 1) - env(\"X\", 1)"
+        );
+        assert_simplified("(x + randint(1, 6))*0 + x - x", "0")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,217 @@
+use crate::expr::{Expr, Op};
+use crate::numeric::RealFunction;
+use crate::resolver::Resolver;
+
+/// The range searched for roots if none is given.
+pub(crate) const DEFAULT_RANGE: (f64, f64) = (-100.0, 100.0);
+
+const SAMPLES: usize = 10_000;
+const MAX_ITERATIONS: usize = 100;
+
+/// Finds all roots of `equation` in `[lo .. hi]`, where the equation is either `a = b` or an expression equal to zero.
+///
+/// The range is sampled for sign changes, which are refined with Newton's method on the symbolic derivative,
+/// falling back to secant and bisection steps. Points where the function only touches zero are polished with Newton's method.
+pub(crate) fn solve(
+    equation: &Expr,
+    var: &str,
+    lo: f64,
+    hi: f64,
+    vars: Option<&dyn Resolver>,
+) -> Result<Vec<f64>, String> {
+    if lo >= hi || !lo.is_finite() || !hi.is_finite() {
+        return Err(format!("Unable to solve in the range [{} .. {}]", lo, hi));
+    }
+    let expr = match equation {
+        Expr::BinOp(Op::Eq, a, b) => Expr::BinOp(Op::Sub, a.clone(), b.clone()),
+        expr => expr.clone(),
+    };
+    let derivative = expr.derivative(var).ok();
+    let mut solver = Solver {
+        f: RealFunction::new(&expr, var, vars),
+        df: derivative
+            .as_ref()
+            .map(|derivative| RealFunction::new(derivative, var, vars)),
+    };
+
+    let step = (hi - lo) / SAMPLES as f64;
+    let samples = (0..=SAMPLES)
+        .map(|i| {
+            let x = if i == SAMPLES {
+                hi
+            } else {
+                lo + i as f64 * step
+            };
+            // points outside of the domain, e.g. of `ln`, are skipped
+            (x, solver.f.eval(x).unwrap_or(f64::NAN))
+        })
+        .collect::<Vec<(f64, f64)>>();
+    if samples.iter().all(|(_, y)| y.is_nan()) {
+        // report why the expression can't be evaluated
+        solver.f.eval(lo)?;
+    }
+
+    let mut roots = Vec::new();
+    let mut failures = 0;
+    for (idx, window) in samples.windows(2).enumerate() {
+        let [(a, fa), (b, fb)] = [window[0], window[1]];
+        if fa == 0.0 {
+            roots.push(a);
+        } else if fa.signum() != fb.signum() && fb != 0.0 && !fb.is_nan() && !fa.is_nan() {
+            if let Some(root) = solver.refine(a, fa, b, fb)? {
+                roots.push(root);
+            }
+        } else if let Some(&(_, fc)) = samples.get(idx + 2) {
+            // a local minimum of |f| may touch zero without changing sign
+            if fb.abs() < fa.abs() && fb.abs() < fc.abs() && fb.signum() == fc.signum() {
+                match solver.polish(b, a, samples[idx + 2].0)? {
+                    Some(root) => roots.push(root),
+                    None => failures += 1,
+                }
+            }
+        }
+    }
+    if samples[SAMPLES].1 == 0.0 {
+        roots.push(hi);
+    }
+
+    roots.sort_by(f64::total_cmp);
+    roots.dedup_by(|a, b| (*a - *b).abs() <= 1e-7 * (1.0 + b.abs()));
+    if roots.is_empty() {
+        return Err(if failures > 0 {
+            format!(
+                "Root finding for {} did not converge in [{} .. {}]",
+                equation, lo, hi
+            )
+        } else {
+            format!("No roots of {} found in [{} .. {}]", equation, lo, hi)
+        });
+    }
+    Ok(roots)
+}
+
+struct Solver<'a> {
+    f: RealFunction<'a>,
+    df: Option<RealFunction<'a>>,
+}
+
+impl Solver<'_> {
+    /// Returns the Newton step `f(x) / f'(x)` at `x`, approximating the derivative if necessary.
+    fn newton_step(&mut self, x: f64, fx: f64) -> Result<f64, String> {
+        let slope = match &mut self.df {
+            Some(df) => df.eval(x)?,
+            None => {
+                let h = 1e-7 * (1.0 + x.abs());
+                (self.f.eval(x + h)? - fx) / h
+            }
+        };
+        Ok(fx / slope)
+    }
+
+    /// Narrows down a sign change of `f` in `[a .. b]`, returning `None` for discontinuities like poles.
+    fn refine(&mut self, mut a: f64, fa: f64, mut b: f64, fb: f64) -> Result<Option<f64>, String> {
+        let scale = 1.0 + fa.abs().min(fb.abs());
+        let mut x = a - fa * (b - a) / (fb - fa);
+        for _ in 0..MAX_ITERATIONS {
+            let fx = self.f.eval(x)?;
+            if fx == 0.0 {
+                return Ok(Some(x));
+            }
+            if fx.signum() == fa.signum() {
+                a = x;
+            } else {
+                b = x;
+            }
+            if (b - a).abs() <= 1e-15 * (1.0 + x.abs()) {
+                break;
+            }
+            let step = self.newton_step(x, fx).unwrap_or(f64::NAN);
+            let next = x - step;
+            // fall back to bisection if Newton's method leaves the bracket
+            if next > a.min(b) && next < a.max(b) {
+                x = next;
+                if step.abs() <= 1e-15 * (1.0 + x.abs()) {
+                    break;
+                }
+            } else {
+                x = a / 2.0 + b / 2.0;
+            }
+        }
+        Ok((self.f.eval(x)?.abs() <= 1e-6 * scale).then_some(x))
+    }
+
+    /// Polishes a root near `x` with Newton's method, which has to stay within `[lo .. hi]`.
+    fn polish(&mut self, mut x: f64, lo: f64, hi: f64) -> Result<Option<f64>, String> {
+        for _ in 0..MAX_ITERATIONS {
+            let fx = self.f.eval(x)?;
+            if fx == 0.0 {
+                return Ok(Some(x));
+            }
+            let step = self.newton_step(x, fx)?;
+            if !step.is_finite() {
+                break;
+            }
+            x -= step;
+            if x < lo || x > hi {
+                return Ok(None);
+            }
+            if step.abs() <= 1e-15 * (1.0 + x.abs()) {
+                break;
+            }
+        }
+        Ok((self.f.eval(x)?.abs() <= 1e-12).then_some(x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve_str(equation: &str, lo: f64, hi: f64) -> Result<Vec<f64>, String> {
+        solve(&Expr::parse(equation)?, "x", lo, hi, None)
+    }
+
+    fn assert_roots(roots: &[f64], expected: &[f64]) {
+        assert_eq!(roots.len(), expected.len(), "{:?} != {:?}", roots, expected);
+        for (root, expected) in roots.iter().zip(expected) {
+            assert!(
+                (root - expected).abs() < 1e-9,
+                "{:?} != {:?}",
+                roots,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn sign_changes() -> Result<(), String> {
+        assert_roots(
+            &solve_str("x^2 = 2", -10.0, 10.0)?,
+            &[-2f64.sqrt(), 2f64.sqrt()],
+        );
+        assert_roots(
+            &solve_str("x^3 - 6*x^2 + 11*x - 6", 0.0, 10.0)?,
+            &[1.0, 2.0, 3.0],
+        );
+        assert_roots(
+            &solve_str("cos(x) = x", -10.0, 10.0)?,
+            &[0.7390851332151607],
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn touching_roots() -> Result<(), String> {
+        assert_roots(&solve_str("(x - 1.3)^2", -10.0, 10.0)?, &[1.3]);
+        Ok(())
+    }
+
+    #[test]
+    fn poles_and_errors() -> Result<(), String> {
+        assert_roots(&solve_str("1 / x = 2", -10.0, 10.0)?, &[0.5]);
+        assert!(solve_str("1 / x", -10.0, 10.0).is_err());
+        assert!(solve_str("x^2 + 1", -10.0, 10.0).is_err());
+        assert!(solve_str("x", 1.0, -1.0).is_err());
+        Ok(())
+    }
+}
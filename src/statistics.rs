@@ -0,0 +1,256 @@
+use crate::functions;
+use crate::type_enum::Type;
+
+/// Calls the statistics function `name` on lists, returning `None` if there is no such function.
+pub(crate) fn call(name: &str, args: &[Type]) -> Option<Result<Type, String>> {
+    let arity = match name {
+        "mean" | "median" | "mode" | "variance" | "pvariance" | "stddev" | "pstddev" => 1..=1,
+        "percentile" | "correlation" | "linreg" => 2..=2,
+        _ => return None,
+    };
+    Some(functions::check_arity(name, args, arity).and_then(|()| apply(name, args)))
+}
+
+fn apply(name: &str, args: &[Type]) -> Result<Type, String> {
+    let xs = numbers(name, &args[0])?;
+    match name {
+        "mean" => Ok(Type::Float(mean(&xs))),
+        "median" => median(name, &args[0], &xs),
+        "mode" => mode(&args[0]),
+        "variance" => Ok(Type::Float(variance(&xs, true)?)),
+        "pvariance" => Ok(Type::Float(variance(&xs, false)?)),
+        "stddev" => Ok(Type::Float(variance(&xs, true)?.sqrt())),
+        "pstddev" => Ok(Type::Float(variance(&xs, false)?.sqrt())),
+        "percentile" => match args[1].as_f64() {
+            Some(p) => Ok(Type::Float(percentile(&xs, p)?)),
+            None => Err(format!(
+                "{} expects a number but got {}",
+                name,
+                args[1].get_type()
+            )),
+        },
+        "correlation" => {
+            let ys = numbers(name, &args[1])?;
+            let (sxy, sxx, syy) = sums_of_squares(&xs, &ys)?;
+            if sxx == 0.0 || syy == 0.0 {
+                return Err("Unable to correlate values without variation".to_string());
+            }
+            Ok(Type::Float(sxy / (sxx * syy).sqrt()))
+        }
+        "linreg" => {
+            let ys = numbers(name, &args[1])?;
+            let (sxy, sxx, _) = sums_of_squares(&xs, &ys)?;
+            if sxx == 0.0 {
+                return Err("Unable to fit a line to values with a single x".to_string());
+            }
+            let slope = sxy / sxx;
+            Ok(Type::List(vec![
+                Type::Float(slope),
+                Type::Float(mean(&ys) - slope * mean(&xs)),
+            ]))
+        }
+        _ => Err(format!("Unknown function {}", name)),
+    }
+}
+
+/// Converts a non-empty list of integers and floats into `f64`s.
+fn numbers(name: &str, list: &Type) -> Result<Vec<f64>, String> {
+    let Type::List(values) = list else {
+        return Err(format!(
+            "{} expects a list but got {}",
+            name,
+            list.get_type()
+        ));
+    };
+    if values.is_empty() {
+        return Err(format!("Unable to calculate the {} of an empty list", name));
+    }
+    values
+        .iter()
+        .map(|value| {
+            value.as_f64().ok_or(format!(
+                "{} expects a list of numbers but got {}",
+                name,
+                value.get_type()
+            ))
+        })
+        .collect()
+}
+
+fn mean(xs: &[f64]) -> f64 {
+    xs.iter().sum::<f64>() / xs.len() as f64
+}
+
+/// The median of an odd number of values keeps its type, otherwise it is the mean of the two middle values.
+fn median(name: &str, list: &Type, xs: &[f64]) -> Result<Type, String> {
+    let mut indices: Vec<usize> = (0..xs.len()).collect();
+    indices.sort_by(|a, b| xs[*a].total_cmp(&xs[*b]));
+    let middle = indices.len() / 2;
+    match list {
+        Type::List(values) if indices.len() % 2 == 1 => Ok(values[indices[middle]].clone()),
+        Type::List(_) => Ok(Type::Float(
+            xs[indices[middle - 1]] / 2.0 + xs[indices[middle]] / 2.0,
+        )),
+        list => Err(format!(
+            "{} expects a list but got {}",
+            name,
+            list.get_type()
+        )),
+    }
+}
+
+/// Returns the most frequent value, the smallest one if there are several.
+fn mode(list: &Type) -> Result<Type, String> {
+    let Type::List(values) = list else {
+        return Err(format!("mode expects a list but got {}", list.get_type()));
+    };
+    let mut counts: Vec<(&Type, usize)> = Vec::new();
+    for value in values {
+        // equal numbers of different types count as the same value
+        match counts
+            .iter_mut()
+            .find(|(v, _)| v.as_f64() == value.as_f64())
+        {
+            Some((_, count)) => *count += 1,
+            None => counts.push((value, 1)),
+        }
+    }
+    counts
+        .into_iter()
+        .max_by(|(a, a_count), (b, b_count)| {
+            a_count.cmp(b_count).then(
+                b.as_f64()
+                    .unwrap_or(f64::NAN)
+                    .total_cmp(&a.as_f64().unwrap_or(f64::NAN)),
+            )
+        })
+        .map(|(value, _)| value.clone())
+        .ok_or("Unable to calculate the mode of an empty list".to_string())
+}
+
+/// Returns the sample variance if `sample` is set, otherwise the population variance.
+fn variance(xs: &[f64], sample: bool) -> Result<f64, String> {
+    if sample && xs.len() < 2 {
+        return Err("Unable to calculate the sample variance of fewer than 2 values".to_string());
+    }
+    let mean = mean(xs);
+    let squares: f64 = xs.iter().map(|x| (x - mean) * (x - mean)).sum();
+    Ok(squares / (xs.len() - sample as usize) as f64)
+}
+
+/// Returns the `p`th percentile, interpolating linearly between the closest ranks.
+fn percentile(xs: &[f64], p: f64) -> Result<f64, String> {
+    if !(0.0..=100.0).contains(&p) {
+        return Err(format!("Percentiles must be between 0 and 100, got {}", p));
+    }
+    let mut sorted = xs.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    Ok(sorted[lower] + (rank - lower as f64) * (sorted[upper] - sorted[lower]))
+}
+
+/// Returns the sums of the products of the deviations from the mean, `(sxy, sxx, syy)`.
+fn sums_of_squares(xs: &[f64], ys: &[f64]) -> Result<(f64, f64, f64), String> {
+    if xs.len() != ys.len() {
+        return Err(format!(
+            "Expected lists of the same length but got {} and {}",
+            xs.len(),
+            ys.len()
+        ));
+    }
+    let (mean_x, mean_y) = (mean(xs), mean(ys));
+    Ok(xs
+        .iter()
+        .zip(ys)
+        .fold((0.0, 0.0, 0.0), |(sxy, sxx, syy), (x, y)| {
+            let (dx, dy) = (x - mean_x, y - mean_y);
+            (sxy + dx * dy, sxx + dx * dx, syy + dy * dy)
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(values: &[f64]) -> Type {
+        Type::List(values.iter().map(|v| Type::Float(*v)).collect())
+    }
+
+    #[test]
+    fn averages() -> Result<(), String> {
+        let ints = Type::List(vec![Type::Int(3), Type::Int(1), Type::Int(2), Type::Int(3)]);
+        assert_eq!(
+            functions::call("mean", vec![ints.clone()])?,
+            Type::Float(2.25)
+        );
+        assert_eq!(
+            functions::call("median", vec![ints.clone()])?,
+            Type::Float(2.5)
+        );
+        assert_eq!(functions::call("mode", vec![ints])?, Type::Int(3));
+        assert_eq!(
+            functions::call(
+                "median",
+                vec![Type::List(vec![
+                    Type::Int(5),
+                    Type::Float(1.5),
+                    Type::Int(2)
+                ])]
+            )?,
+            Type::Int(2)
+        );
+        assert_eq!(
+            functions::call("mean", vec![Type::List(Vec::new())]),
+            Err("Unable to calculate the mean of an empty list".to_string())
+        );
+        assert!(functions::call("mean", vec![Type::List(vec![Type::from("a")])]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn spread() -> Result<(), String> {
+        let xs = list(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+        assert_eq!(
+            functions::call("pvariance", vec![xs.clone()])?,
+            Type::Float(4.0)
+        );
+        assert_eq!(
+            functions::call("pstddev", vec![xs.clone()])?,
+            Type::Float(2.0)
+        );
+        assert_eq!(
+            functions::call("variance", vec![xs.clone()])?,
+            Type::Float(32.0 / 7.0)
+        );
+        assert!(functions::call("stddev", vec![list(&[1.0])]).is_err());
+
+        assert_eq!(
+            functions::call("percentile", vec![xs.clone(), Type::Int(50)])?,
+            Type::Float(4.5)
+        );
+        assert_eq!(
+            functions::call("percentile", vec![xs.clone(), Type::Int(100)])?,
+            Type::Float(9.0)
+        );
+        assert!(functions::call("percentile", vec![xs, Type::Int(101)]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn regression() -> Result<(), String> {
+        let xs = list(&[1.0, 2.0, 3.0, 4.0]);
+        let ys = list(&[3.0, 5.0, 7.0, 9.0]);
+        assert_eq!(
+            functions::call("correlation", vec![xs.clone(), ys.clone()])?,
+            Type::Float(1.0)
+        );
+        assert_eq!(
+            functions::call("linreg", vec![xs.clone(), ys])?,
+            list(&[2.0, 1.0])
+        );
+        assert!(functions::call("linreg", vec![xs.clone(), list(&[1.0])]).is_err());
+        assert!(functions::call("correlation", vec![xs, list(&[1.0, 1.0, 1.0, 1.0])]).is_err());
+        Ok(())
+    }
+}
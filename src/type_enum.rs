@@ -1,5 +1,10 @@
 use std::fmt;
-use std::ops::{Add, Div, Mul, Rem, Sub};
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+use crate::expr::Function;
+use crate::interval::Interval;
+use crate::measurement::Measurement;
+use crate::polynomial::Polynomial;
 
 /// Represents different types that can be used in mathematical operations.
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
@@ -10,6 +15,16 @@ pub enum Type {
     Float(f64),
     /// Represents a String.
     String(String),
+    /// Represents a closed interval of real numbers, e.g. `[1.9 .. 2.1]`.
+    Interval(Interval),
+    /// Represents a measured value with its standard uncertainty, e.g. `9.81 ± 0.02`.
+    Measurement(Measurement),
+    /// Represents a list of values, e.g. the roots found by `solve`.
+    List(Vec<Type>),
+    /// Represents a polynomial in `x`, e.g. `x^2 - 3x + 2`.
+    Polynomial(Polynomial),
+    /// Represents a user defined function, e.g. `(x) -> x^2`.
+    Function(Function),
 }
 
 impl Type {
@@ -25,6 +40,11 @@ impl Type {
     /// * If the variant is `Type::Int`, the return value is "i32" representing a 32-bit signed integer type.
     /// * If the variant is `Type::Float`, the return value is "f64" representing a 64-bit floating-point type.
     /// * If the variant is `Type::String`, the return value is "String" indicating a string type.
+    /// * If the variant is `Type::Interval`, the return value is "Interval" indicating an interval type.
+    /// * If the variant is `Type::Measurement`, the return value is "Measurement" indicating a value with uncertainty.
+    /// * If the variant is `Type::List`, the return value is "List" indicating a list of values.
+    /// * If the variant is `Type::Polynomial`, the return value is "Polynomial" indicating a polynomial.
+    /// * If the variant is `Type::Function`, the return value is "Function" indicating a user defined function.
     ///
     /// # Example
     ///
@@ -44,6 +64,85 @@ impl Type {
             Type::Int(_) => "i32",
             Type::Float(_) => "f64",
             Type::String(_) => "String",
+            Type::Interval(_) => "Interval",
+            Type::Measurement(_) => "Measurement",
+            Type::List(_) => "List",
+            Type::Polynomial(_) => "Polynomial",
+            Type::Function(_) => "Function",
+        }
+    }
+
+    /// Raises the value to the power of `exponent`.
+    ///
+    /// Integer powers of integers stay integers as long as the exponent is not negative
+    /// and the result fits into an `i32`, otherwise the result is a float.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_little_eval::type_enum::Type;
+    ///
+    /// assert_eq!(Type::Int(2).pow(Type::Int(10)), Ok(Type::Int(1024)));
+    /// assert_eq!(Type::Int(2).pow(Type::Int(-1)), Ok(Type::Float(0.5)));
+    /// assert_eq!(Type::Float(4.0).pow(Type::Float(0.5)), Ok(Type::Float(2.0)));
+    /// ```
+    pub fn pow(self, exponent: Type) -> Result<Type, String> {
+        match (self, exponent) {
+            (Type::Int(a), Type::Int(b)) => {
+                match u32::try_from(b).ok().and_then(|b| a.checked_pow(b)) {
+                    Some(value) => Ok(Type::Int(value)),
+                    None => float_pow(a as f64, b as f64),
+                }
+            }
+            (Type::Int(a), Type::Float(b)) => float_pow(a as f64, b),
+            (Type::Float(a), Type::Float(b)) => float_pow(a, b),
+            (Type::Float(a), Type::Int(b)) => float_pow(a, b as f64),
+            (Type::Interval(a), Type::Int(b)) => Ok(Type::Interval(a.powi(b)?)),
+            (Type::Interval(a), Type::Float(b))
+                if b.fract() == 0.0 && b.abs() <= i32::MAX as f64 =>
+            {
+                Ok(Type::Interval(a.powi(b as i32)?))
+            }
+            (Type::Measurement(a), Type::Measurement(b)) => Ok(Type::Measurement(a.pow(b)?)),
+            (Type::Measurement(a), Type::Int(b)) => {
+                Ok(Type::Measurement(a.pow(Measurement::exact(b as f64))?))
+            }
+            (Type::Measurement(a), Type::Float(b)) => {
+                Ok(Type::Measurement(a.pow(Measurement::exact(b))?))
+            }
+            (Type::Int(a), Type::Measurement(b)) => {
+                Ok(Type::Measurement(Measurement::exact(a as f64).pow(b)?))
+            }
+            (Type::Float(a), Type::Measurement(b)) => {
+                Ok(Type::Measurement(Measurement::exact(a).pow(b)?))
+            }
+            (Type::Polynomial(a), Type::Int(b)) if b >= 0 => {
+                Ok(Type::Polynomial(a.powi(b as u32)?))
+            }
+            (a, b) => Err(format!(
+                "Unable to raise {} to the power of {}",
+                a.get_type(),
+                b.get_type()
+            )),
+        }
+    }
+
+    /// Returns the value as a `f64` if the variant is `Type::Int` or `Type::Float`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_little_eval::type_enum::Type;
+    ///
+    /// assert_eq!(Type::Int(2).as_f64(), Some(2.0));
+    /// assert_eq!(Type::Float(2.5).as_f64(), Some(2.5));
+    /// assert_eq!(Type::from("two").as_f64(), None);
+    /// ```
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Type::Int(value) => Some(*value as f64),
+            Type::Float(value) => Some(*value),
+            _ => None,
         }
     }
 }
@@ -54,6 +153,10 @@ impl From<&str> for Type {
             Type::Int(int_value)
         } else if let Ok(float_value) = value.replace(',', ".").parse::<f64>() {
             Type::Float(float_value)
+        } else if let Some(interval) = parse_interval(value) {
+            Type::Interval(interval)
+        } else if let Some(measurement) = parse_measurement(value) {
+            Type::Measurement(measurement)
         } else {
             Type::String(value.to_string())
         }
@@ -66,6 +169,20 @@ impl fmt::Display for Type {
             Type::Int(value) => write!(f, "{}", value),
             Type::Float(value) => write!(f, "{}", value),
             Type::String(value) => write!(f, "\"{}\"", value),
+            Type::Interval(value) => write!(f, "{}", value),
+            Type::Measurement(value) => write!(f, "{}", value),
+            Type::Polynomial(value) => write!(f, "{}", value),
+            Type::Function(value) => write!(f, "{}", value),
+            Type::List(values) => {
+                write!(f, "[")?;
+                for (idx, value) in values.iter().enumerate() {
+                    if idx != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
         }
     }
 }
@@ -75,7 +192,9 @@ impl Add for Type {
 
     fn add(self, other: Type) -> Self::Output {
         match (self, other) {
-            (Type::Int(a), Type::Int(b)) => Ok(Type::Int(a + b)),
+            (Type::Int(a), Type::Int(b)) => Ok(a
+                .checked_add(b)
+                .map_or(Type::Float(a as f64 + b as f64), Type::Int)),
             (Type::Int(a), Type::Float(b)) => Ok(Type::Float(a as f64 + b)),
             (Type::Int(a), Type::String(b)) => Ok(Type::String(format!("{}{}", a, b))),
             (Type::Float(a), Type::Float(b)) => Ok(Type::Float(a + b)),
@@ -84,6 +203,42 @@ impl Add for Type {
             (Type::String(a), Type::String(b)) => Ok(Type::String(a + &b)),
             (Type::String(a), Type::Int(b)) => Ok(Type::String(format!("{}{}", a, b))),
             (Type::String(a), Type::Float(b)) => Ok(Type::String(format!("{}{}", a, b))),
+            (Type::Interval(a), Type::Interval(b)) => Ok(Type::Interval(a + b)),
+            (Type::Interval(a), Type::Int(b)) => Ok(Type::Interval(a + Interval::point(b as f64))),
+            (Type::Interval(a), Type::Float(b)) => Ok(Type::Interval(a + Interval::point(b))),
+            (Type::Int(a), Type::Interval(b)) => Ok(Type::Interval(Interval::point(a as f64) + b)),
+            (Type::Float(a), Type::Interval(b)) => Ok(Type::Interval(Interval::point(a) + b)),
+            (Type::Measurement(a), Type::Measurement(b)) => Ok(Type::Measurement(a + b)),
+            (Type::Measurement(a), Type::Int(b)) => {
+                Ok(Type::Measurement(a + Measurement::exact(b as f64)))
+            }
+            (Type::Measurement(a), Type::Float(b)) => {
+                Ok(Type::Measurement(a + Measurement::exact(b)))
+            }
+            (Type::Int(a), Type::Measurement(b)) => {
+                Ok(Type::Measurement(Measurement::exact(a as f64) + b))
+            }
+            (Type::Float(a), Type::Measurement(b)) => {
+                Ok(Type::Measurement(Measurement::exact(a) + b))
+            }
+            (Type::Polynomial(a), Type::Polynomial(b)) => Ok(Type::Polynomial(a + b)),
+            (Type::Polynomial(a), Type::Int(b)) => {
+                Ok(Type::Polynomial(a + Polynomial::constant(b as f64)))
+            }
+            (Type::Polynomial(a), Type::Float(b)) => {
+                Ok(Type::Polynomial(a + Polynomial::constant(b)))
+            }
+            (Type::Int(a), Type::Polynomial(b)) => {
+                Ok(Type::Polynomial(Polynomial::constant(a as f64) + b))
+            }
+            (Type::Float(a), Type::Polynomial(b)) => {
+                Ok(Type::Polynomial(Polynomial::constant(a) + b))
+            }
+            (a, b) => Err(format!(
+                "Unable to add {} to {}",
+                b.get_type(),
+                a.get_type()
+            )),
         }
     }
 }
@@ -93,10 +248,43 @@ impl Sub for Type {
 
     fn sub(self, other: Self) -> Self::Output {
         match (self, other) {
-            (Type::Int(a), Type::Int(b)) => Ok(Type::Int(a - b)),
+            (Type::Int(a), Type::Int(b)) => Ok(a
+                .checked_sub(b)
+                .map_or(Type::Float(a as f64 - b as f64), Type::Int)),
             (Type::Int(a), Type::Float(b)) => Ok(Type::Float(a as f64 - b)),
             (Type::Float(a), Type::Float(b)) => Ok(Type::Float(a - b)),
             (Type::Float(a), Type::Int(b)) => Ok(Type::Float(a - b as f64)),
+            (Type::Interval(a), Type::Interval(b)) => Ok(Type::Interval(a - b)),
+            (Type::Interval(a), Type::Int(b)) => Ok(Type::Interval(a - Interval::point(b as f64))),
+            (Type::Interval(a), Type::Float(b)) => Ok(Type::Interval(a - Interval::point(b))),
+            (Type::Int(a), Type::Interval(b)) => Ok(Type::Interval(Interval::point(a as f64) - b)),
+            (Type::Float(a), Type::Interval(b)) => Ok(Type::Interval(Interval::point(a) - b)),
+            (Type::Measurement(a), Type::Measurement(b)) => Ok(Type::Measurement(a - b)),
+            (Type::Measurement(a), Type::Int(b)) => {
+                Ok(Type::Measurement(a - Measurement::exact(b as f64)))
+            }
+            (Type::Measurement(a), Type::Float(b)) => {
+                Ok(Type::Measurement(a - Measurement::exact(b)))
+            }
+            (Type::Int(a), Type::Measurement(b)) => {
+                Ok(Type::Measurement(Measurement::exact(a as f64) - b))
+            }
+            (Type::Float(a), Type::Measurement(b)) => {
+                Ok(Type::Measurement(Measurement::exact(a) - b))
+            }
+            (Type::Polynomial(a), Type::Polynomial(b)) => Ok(Type::Polynomial(a - b)),
+            (Type::Polynomial(a), Type::Int(b)) => {
+                Ok(Type::Polynomial(a - Polynomial::constant(b as f64)))
+            }
+            (Type::Polynomial(a), Type::Float(b)) => {
+                Ok(Type::Polynomial(a - Polynomial::constant(b)))
+            }
+            (Type::Int(a), Type::Polynomial(b)) => {
+                Ok(Type::Polynomial(Polynomial::constant(a as f64) - b))
+            }
+            (Type::Float(a), Type::Polynomial(b)) => {
+                Ok(Type::Polynomial(Polynomial::constant(a) - b))
+            }
             (a, b) => Err(format!(
                 "Unable to substract {} from {}",
                 b.get_type(),
@@ -111,12 +299,45 @@ impl Mul for Type {
 
     fn mul(self, other: Self) -> Self::Output {
         match (self, other) {
-            (Type::Int(a), Type::Int(b)) => Ok(Type::Int(a * b)),
+            (Type::Int(a), Type::Int(b)) => Ok(a
+                .checked_mul(b)
+                .map_or(Type::Float(a as f64 * b as f64), Type::Int)),
             (Type::Int(a), Type::Float(b)) => Ok(Type::Float(a as f64 * b)),
             (Type::Int(a), Type::String(b)) => Ok(Type::String(b.repeat(a as usize))),
             (Type::Float(a), Type::Float(b)) => Ok(Type::Float(a * b)),
             (Type::Float(a), Type::Int(b)) => Ok(Type::Float(a * b as f64)),
             (Type::String(a), Type::Int(b)) => Ok(Type::String(a.repeat(b as usize))),
+            (Type::Interval(a), Type::Interval(b)) => Ok(Type::Interval(a * b)),
+            (Type::Interval(a), Type::Int(b)) => Ok(Type::Interval(a * Interval::point(b as f64))),
+            (Type::Interval(a), Type::Float(b)) => Ok(Type::Interval(a * Interval::point(b))),
+            (Type::Int(a), Type::Interval(b)) => Ok(Type::Interval(Interval::point(a as f64) * b)),
+            (Type::Float(a), Type::Interval(b)) => Ok(Type::Interval(Interval::point(a) * b)),
+            (Type::Measurement(a), Type::Measurement(b)) => Ok(Type::Measurement(a * b)),
+            (Type::Measurement(a), Type::Int(b)) => {
+                Ok(Type::Measurement(a * Measurement::exact(b as f64)))
+            }
+            (Type::Measurement(a), Type::Float(b)) => {
+                Ok(Type::Measurement(a * Measurement::exact(b)))
+            }
+            (Type::Int(a), Type::Measurement(b)) => {
+                Ok(Type::Measurement(Measurement::exact(a as f64) * b))
+            }
+            (Type::Float(a), Type::Measurement(b)) => {
+                Ok(Type::Measurement(Measurement::exact(a) * b))
+            }
+            (Type::Polynomial(a), Type::Polynomial(b)) => Ok(Type::Polynomial(a * b)),
+            (Type::Polynomial(a), Type::Int(b)) => {
+                Ok(Type::Polynomial(a * Polynomial::constant(b as f64)))
+            }
+            (Type::Polynomial(a), Type::Float(b)) => {
+                Ok(Type::Polynomial(a * Polynomial::constant(b)))
+            }
+            (Type::Int(a), Type::Polynomial(b)) => {
+                Ok(Type::Polynomial(Polynomial::constant(a as f64) * b))
+            }
+            (Type::Float(a), Type::Polynomial(b)) => {
+                Ok(Type::Polynomial(Polynomial::constant(a) * b))
+            }
             (a, b) => Err(format!(
                 "Unable to multiply {} with {}",
                 a.get_type(),
@@ -135,7 +356,8 @@ impl Div for Type {
                 if b == 0 {
                     Err("Unable to divide by zero".to_string())
                 } else {
-                    Ok(Type::Int(a / b))
+                    Ok(a.checked_div(b)
+                        .map_or(Type::Float(a as f64 / b as f64), Type::Int))
                 }
             }
             (Type::Int(a), Type::Float(b)) => {
@@ -159,6 +381,41 @@ impl Div for Type {
                     Ok(Type::Float(a / b as f64))
                 }
             }
+            (Type::Interval(a), Type::Interval(b)) => Ok(Type::Interval((a / b)?)),
+            (Type::Interval(a), Type::Int(b)) => {
+                Ok(Type::Interval((a / Interval::point(b as f64))?))
+            }
+            (Type::Interval(a), Type::Float(b)) => Ok(Type::Interval((a / Interval::point(b))?)),
+            (Type::Int(a), Type::Interval(b)) => {
+                Ok(Type::Interval((Interval::point(a as f64) / b)?))
+            }
+            (Type::Float(a), Type::Interval(b)) => Ok(Type::Interval((Interval::point(a) / b)?)),
+            (Type::Measurement(a), Type::Measurement(b)) => Ok(Type::Measurement((a / b)?)),
+            (Type::Measurement(a), Type::Int(b)) => {
+                Ok(Type::Measurement((a / Measurement::exact(b as f64))?))
+            }
+            (Type::Measurement(a), Type::Float(b)) => {
+                Ok(Type::Measurement((a / Measurement::exact(b))?))
+            }
+            (Type::Int(a), Type::Measurement(b)) => {
+                Ok(Type::Measurement((Measurement::exact(a as f64) / b)?))
+            }
+            (Type::Float(a), Type::Measurement(b)) => {
+                Ok(Type::Measurement((Measurement::exact(a) / b)?))
+            }
+            (Type::Polynomial(a), Type::Polynomial(b)) => Ok(Type::Polynomial((a / b)?)),
+            (Type::Polynomial(a), Type::Int(b)) => {
+                Ok(Type::Polynomial((a / Polynomial::constant(b as f64))?))
+            }
+            (Type::Polynomial(a), Type::Float(b)) => {
+                Ok(Type::Polynomial((a / Polynomial::constant(b))?))
+            }
+            (Type::Int(a), Type::Polynomial(b)) => {
+                Ok(Type::Polynomial((Polynomial::constant(a as f64) / b)?))
+            }
+            (Type::Float(a), Type::Polynomial(b)) => {
+                Ok(Type::Polynomial((Polynomial::constant(a) / b)?))
+            }
             (a, b) => Err(format!(
                 "Unable to divide {} by {}",
                 a.get_type(),
@@ -177,7 +434,8 @@ impl Rem for Type {
                 if b == 0 {
                     Err("Unable to calculate the remainder with a divisor of zero".to_string())
                 } else {
-                    Ok(Type::Int(a % b))
+                    // i32::MIN % -1 overflows, although the remainder is 0
+                    Ok(Type::Int(a.checked_rem(b).unwrap_or(0)))
                 }
             }
             (Type::Int(a), Type::Float(b)) => {
@@ -201,6 +459,32 @@ impl Rem for Type {
                     Ok(Type::Float(a % b as f64))
                 }
             }
+            (Type::Measurement(a), Type::Measurement(b)) => Ok(Type::Measurement((a % b)?)),
+            (Type::Measurement(a), Type::Int(b)) => {
+                Ok(Type::Measurement((a % Measurement::exact(b as f64))?))
+            }
+            (Type::Measurement(a), Type::Float(b)) => {
+                Ok(Type::Measurement((a % Measurement::exact(b))?))
+            }
+            (Type::Int(a), Type::Measurement(b)) => {
+                Ok(Type::Measurement((Measurement::exact(a as f64) % b)?))
+            }
+            (Type::Float(a), Type::Measurement(b)) => {
+                Ok(Type::Measurement((Measurement::exact(a) % b)?))
+            }
+            (Type::Polynomial(a), Type::Polynomial(b)) => Ok(Type::Polynomial((a % b)?)),
+            (Type::Polynomial(a), Type::Int(b)) => {
+                Ok(Type::Polynomial((a % Polynomial::constant(b as f64))?))
+            }
+            (Type::Polynomial(a), Type::Float(b)) => {
+                Ok(Type::Polynomial((a % Polynomial::constant(b))?))
+            }
+            (Type::Int(a), Type::Polynomial(b)) => {
+                Ok(Type::Polynomial((Polynomial::constant(a as f64) % b)?))
+            }
+            (Type::Float(a), Type::Polynomial(b)) => {
+                Ok(Type::Polynomial((Polynomial::constant(a) % b)?))
+            }
             (a, b) => Err(format!(
                 "Cannot perform modulo operation between {} and {}",
                 a.get_type(),
@@ -210,6 +494,48 @@ impl Rem for Type {
     }
 }
 
+impl Neg for Type {
+    type Output = Result<Type, String>;
+
+    fn neg(self) -> Self::Output {
+        match self {
+            Type::Int(a) => Ok(a.checked_neg().map_or(Type::Float(-(a as f64)), Type::Int)),
+            Type::Float(a) => Ok(Type::Float(-a)),
+            Type::Interval(a) => Ok(Type::Interval(-a)),
+            Type::Measurement(a) => Ok(Type::Measurement(-a)),
+            Type::Polynomial(a) => Ok(Type::Polynomial(-a)),
+            a => Err(format!("Unable to negate {}", a.get_type())),
+        }
+    }
+}
+
+fn float_pow(a: f64, b: f64) -> Result<Type, String> {
+    let value = a.powf(b);
+    if value.is_nan() && !a.is_nan() && !b.is_nan() {
+        return Err("Unable to raise a negative number to a fractional power".to_string());
+    }
+    Ok(Type::Float(value))
+}
+
+/// Parses an interval written as `[lo .. hi]`.
+fn parse_interval(value: &str) -> Option<Interval> {
+    let (lo, hi) = value
+        .strip_prefix('[')?
+        .strip_suffix(']')?
+        .split_once("..")?;
+    let lo = lo.trim().replace(',', ".").parse::<f64>().ok()?;
+    let hi = hi.trim().replace(',', ".").parse::<f64>().ok()?;
+    Interval::new(lo, hi).ok()
+}
+
+/// Parses a measurement written as `value ± uncertainty`.
+fn parse_measurement(value: &str) -> Option<Measurement> {
+    let (value, uncertainty) = value.split_once('±')?;
+    let value = value.trim().replace(',', ".").parse::<f64>().ok()?;
+    let uncertainty = uncertainty.trim().replace(',', ".").parse::<f64>().ok()?;
+    Measurement::new(value, uncertainty).ok()
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -247,4 +573,47 @@ mod tests {
         }
         Err(format!("Couldn't parse {s} as a float"))
     }
+    #[test]
+    fn parse_interval() -> Result<(), String> {
+        let s = "[1.9 .. 2.1]";
+        if let Type::Interval(val) = Type::from(s) {
+            if val == Interval::new(1.9, 2.1)? {
+                return Ok(());
+            }
+            return Err(format!("Error parsing {s} as an interval, value changed"));
+        }
+        Err(format!("Couldn't parse {s} as an interval"))
+    }
+    #[test]
+    fn overflow_promotes_to_float() -> Result<(), String> {
+        assert_eq!(
+            (Type::Int(i32::MAX) + Type::Int(1))?,
+            Type::Float(2147483648.0)
+        );
+        assert_eq!(
+            (Type::Int(i32::MIN) - Type::Int(1))?,
+            Type::Float(-2147483649.0)
+        );
+        assert_eq!(
+            (Type::Int(65536) * Type::Int(65536))?,
+            Type::Float(4294967296.0)
+        );
+        assert_eq!(
+            (Type::Int(i32::MIN) / Type::Int(-1))?,
+            Type::Float(2147483648.0)
+        );
+        assert_eq!((Type::Int(i32::MIN) % Type::Int(-1))?, Type::Int(0));
+        Ok(())
+    }
+    #[test]
+    fn parse_measurement() -> Result<(), String> {
+        let s = "9.81 ± 0.02";
+        if let Type::Measurement(val) = Type::from(s) {
+            if val == Measurement::new(9.81, 0.02)? {
+                return Ok(());
+            }
+            return Err(format!("Error parsing {s} as a measurement, value changed"));
+        }
+        Err(format!("Couldn't parse {s} as a measurement"))
+    }
 }
@@ -0,0 +1,289 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::expr::Expr;
+use crate::resolver::Resolver;
+use crate::type_enum::Type;
+
+/// Variables with spreadsheet-style formulas, which are recomputed whenever a variable they depend on changes.
+///
+/// Formulas are recomputed in topological order, so every formula sees the recomputed values of the formulas it depends on.
+/// Defining a formula that depends on itself, directly or through other formulas, is rejected.
+///
+/// # Example
+///
+/// ```
+/// use my_little_eval::{eval, workspace::Workspace, type_enum::Type};
+///
+/// let mut workspace = Workspace::new();
+/// workspace.set("price", Type::Int(3)).unwrap();
+/// workspace.set("qty", Type::Int(2)).unwrap();
+/// workspace.define("total", "price * qty").unwrap();
+/// assert_eq!(workspace.values()["total"], Type::Int(6));
+///
+/// workspace.set("qty", Type::Int(5)).unwrap();
+/// assert_eq!(eval("total + 1", Some(&workspace)), Ok(Type::Int(16)));
+///
+/// assert_eq!(
+///     workspace.define("price", "total / qty"),
+///     Err("Cyclic dependency: price -> total -> price".to_string())
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Workspace {
+    values: HashMap<String, Type>,
+    formulas: BTreeMap<String, Formula>,
+}
+
+#[derive(Debug, Clone)]
+struct Formula {
+    expr: Expr,
+    dependencies: BTreeSet<String>,
+}
+
+impl Workspace {
+    /// Creates an empty workspace.
+    pub fn new() -> Workspace {
+        Workspace::default()
+    }
+
+    /// Sets the variable `name` to `value`, replacing its formula if it had one,
+    /// and recomputes the formulas depending on it.
+    ///
+    /// The value is set even if recomputing a formula fails, in which case the first error is returned
+    /// and the variables of the failed formulas are removed until they can be recomputed.
+    pub fn set(&mut self, name: &str, value: Type) -> Result<(), String> {
+        self.formulas.remove(name);
+        self.values.insert(name.to_string(), value);
+        self.recompute(name)
+    }
+
+    /// Defines the variable `name` by the formula `equation`, computes it
+    /// and recomputes the formulas depending on it.
+    ///
+    /// Returns an error without changing anything if the equation can't be parsed or would create a cyclic dependency.
+    /// If only computing a formula fails, the formula is kept and computed again once its dependencies change.
+    pub fn define(&mut self, name: &str, equation: &str) -> Result<(), String> {
+        let expr = Expr::parse(equation)?;
+        let dependencies = expr.free_variables();
+        for dependency in &dependencies {
+            if let Some(path) = self.path(dependency, name) {
+                return Err(format!(
+                    "Cyclic dependency: {} -> {}",
+                    name,
+                    path.join(" -> ")
+                ));
+            }
+        }
+        self.formulas
+            .insert(name.to_string(), Formula { expr, dependencies });
+        self.recompute(name)
+    }
+
+    /// Removes the variable `name` along with its formula and recomputes the formulas depending on it.
+    pub fn remove(&mut self, name: &str) -> Result<Option<Type>, String> {
+        self.formulas.remove(name);
+        let value = self.values.remove(name);
+        self.recompute(name)?;
+        Ok(value)
+    }
+
+    /// Returns the current values of all variables.
+    pub fn values(&self) -> &HashMap<String, Type> {
+        &self.values
+    }
+
+    /// Returns the formula of the variable `name`, or `None` if it has a plain value or doesn't exist.
+    pub fn formula(&self, name: &str) -> Option<&Expr> {
+        self.formulas.get(name).map(|formula| &formula.expr)
+    }
+
+    /// Returns the names and formulas of all variables defined by a formula, sorted by name.
+    pub fn formulas(&self) -> impl Iterator<Item = (&str, &Expr)> {
+        self.formulas
+            .iter()
+            .map(|(name, formula)| (name.as_str(), &formula.expr))
+    }
+
+    /// Restores the value and formula of the variable `name` exactly, without recomputing any formulas.
+    ///
+    /// Used to undo changes, where all variables that changed together are restored.
+    pub(crate) fn restore(&mut self, name: &str, value: Option<Type>, formula: Option<Expr>) {
+        match formula {
+            Some(expr) => {
+                let dependencies = expr.free_variables();
+                self.formulas
+                    .insert(name.to_string(), Formula { expr, dependencies });
+            }
+            None => {
+                self.formulas.remove(name);
+            }
+        }
+        match value {
+            Some(value) => self.values.insert(name.to_string(), value),
+            None => self.values.remove(name),
+        };
+    }
+
+    /// Returns the path of formulas from `from` to `to` through their dependencies, if there is one.
+    fn path<'a>(&'a self, from: &'a str, to: &str) -> Option<Vec<String>> {
+        fn visit<'a>(
+            workspace: &'a Workspace,
+            from: &'a str,
+            to: &str,
+            visited: &mut HashSet<&'a str>,
+        ) -> Option<Vec<String>> {
+            if from == to {
+                return Some(vec![to.to_string()]);
+            }
+            // formulas can share dependencies, each one only needs to be searched once
+            if !visited.insert(from) {
+                return None;
+            }
+            let formula = workspace.formulas.get(from)?;
+            formula.dependencies.iter().find_map(|dependency| {
+                let mut path = visit(workspace, dependency, to, visited)?;
+                path.insert(0, from.to_string());
+                Some(path)
+            })
+        }
+
+        visit(self, from, to, &mut HashSet::new())
+    }
+
+    /// Returns the names of all formulas, ordered such that each formula comes after its dependencies.
+    fn topological_order(&self) -> Vec<&str> {
+        fn visit<'a>(
+            workspace: &'a Workspace,
+            name: &'a str,
+            visited: &mut HashSet<&'a str>,
+            order: &mut Vec<&'a str>,
+        ) {
+            let Some(formula) = workspace.formulas.get(name) else {
+                return;
+            };
+            if !visited.insert(name) {
+                return;
+            }
+            for dependency in &formula.dependencies {
+                visit(workspace, dependency, visited, order);
+            }
+            order.push(name);
+        }
+
+        let mut visited = HashSet::new();
+        let mut order = Vec::new();
+        for name in self.formulas.keys() {
+            visit(self, name, &mut visited, &mut order);
+        }
+        order
+    }
+
+    /// Recomputes the formula of `changed`, if it has one, and all formulas depending on it.
+    fn recompute(&mut self, changed: &str) -> Result<(), String> {
+        let mut dirty = BTreeSet::from([changed.to_string()]);
+        let mut result = Ok(());
+        let order: Vec<String> = self
+            .topological_order()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        for name in order {
+            let formula = &self.formulas[&name];
+            if name != changed && formula.dependencies.is_disjoint(&dirty) {
+                continue;
+            }
+            match formula.expr.eval(Some(&self.values)) {
+                Ok(value) => {
+                    self.values.insert(name.clone(), value);
+                }
+                Err(error) => {
+                    self.values.remove(&name);
+                    if result.is_ok() {
+                        result = Err(format!("Unable to compute {}: {}", name, error));
+                    }
+                }
+            }
+            dirty.insert(name);
+        }
+        result
+    }
+}
+
+impl Resolver for Workspace {
+    fn get(&self, name: &str) -> Option<Type> {
+        self.values.get(name).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recomputes_in_topological_order() -> Result<(), String> {
+        let mut workspace = Workspace::new();
+        workspace.define("d", "b + c")?;
+        workspace.define("b", "a * 2")?;
+        workspace.define("c", "b + 1")?;
+        workspace.set("a", Type::Int(1))?;
+        assert_eq!(workspace.values()["d"], Type::Int(5));
+
+        workspace.set("a", Type::Int(10))?;
+        assert_eq!(workspace.values()["b"], Type::Int(20));
+        assert_eq!(workspace.values()["c"], Type::Int(21));
+        assert_eq!(workspace.values()["d"], Type::Int(41));
+
+        workspace.set("b", Type::Int(0))?;
+        assert_eq!(workspace.formula("b"), None);
+        assert_eq!(workspace.values()["d"], Type::Int(1));
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_cycles() -> Result<(), String> {
+        let mut workspace = Workspace::new();
+        assert_eq!(
+            workspace.define("x", "x + 1"),
+            Err("Cyclic dependency: x -> x".to_string())
+        );
+        workspace.define("b", "a + 1")?;
+        workspace.define("c", "b + 1")?;
+        assert_eq!(
+            workspace.define("a", "c * 2"),
+            Err("Cyclic dependency: a -> c -> b -> a".to_string())
+        );
+        assert_eq!(workspace.formula("a"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn shared_dependencies() -> Result<(), String> {
+        // a chain of diamonds, where both a_i and b_i depend on x_(i-1)
+        let mut workspace = Workspace::new();
+        workspace.set("x0", Type::Int(1))?;
+        for i in 1..=60 {
+            workspace.define(&format!("a{}", i), &format!("x{}", i - 1))?;
+            workspace.define(&format!("b{}", i), &format!("x{}", i - 1))?;
+            workspace.define(&format!("x{}", i), &format!("a{} + b{} - x{}", i, i, i - 1))?;
+        }
+        workspace.define("top", "x60 * 2")?;
+        assert_eq!(workspace.values()["top"], Type::Int(2));
+        let error = workspace.define("x0", "top").unwrap_err();
+        assert!(error.starts_with("Cyclic dependency: x0 -> top -> x60 -> a60 -> x59"));
+        assert!(error.ends_with("x1 -> a1 -> x0"));
+        Ok(())
+    }
+
+    #[test]
+    fn keeps_failed_formulas() -> Result<(), String> {
+        let mut workspace = Workspace::new();
+        workspace.set("a", Type::from("text"))?;
+        assert!(workspace.define("b", "a - 1").is_err());
+        assert_eq!(workspace.values().get("b"), None);
+        workspace.set("a", Type::Int(3))?;
+        assert_eq!(workspace.values()["b"], Type::Int(2));
+        assert!(workspace.remove("a").is_err());
+        assert_eq!(workspace.values().get("b"), None);
+        Ok(())
+    }
+}